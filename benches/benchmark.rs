@@ -1,6 +1,8 @@
-use convex_hulls::{andrew, akl, quickhull, jarvis, chan, quickhull3d};
-use convex_hulls::util::{get_test_vector_2d, get_test_vector_3d};
+use convex_hulls::{andrew, graham, akl, quickhull, jarvis, chan, kirkpatrick, quickhull3d, from_flat, ConvexPolygon, IncrementalHull2d, Point2};
+use convex_hulls::util::{get_test_vector_2d, get_test_vector_gaussian, get_test_vector_3d};
 use criterion::{Criterion, criterion_group, criterion_main};
+#[cfg(feature = "parallel")]
+use convex_hulls::par_quickhull;
 
 fn bench_andrew_2048(c: &mut Criterion) {
     let v = get_test_vector_2d(2048);
@@ -14,6 +16,18 @@ fn bench_andrew_akl_2048(c: &mut Criterion) {
     c.bench_function("andrew+akl", |b| b.iter(|| andrew(&akl(&v))));
 }
 
+fn bench_graham_2048(c: &mut Criterion) {
+    let v = get_test_vector_2d(2048);
+
+    c.bench_function("graham", |b| b.iter(|| graham(&v)));
+}
+
+fn bench_graham_akl_2048(c: &mut Criterion) {
+    let v = get_test_vector_2d(2048);
+
+    c.bench_function("graham+akl", |b| b.iter(|| graham(&akl(&v))));
+}
+
 fn bench_quickhull_2048(c: &mut Criterion) {
     let v = get_test_vector_2d(2048);
 
@@ -26,6 +40,27 @@ fn bench_quickhull_akl_2048(c: &mut Criterion) {
     c.bench_function("quickhull+akl", |b| b.iter(|| quickhull(&akl(&v))));
 }
 
+fn bench_quickhull_1000000(c: &mut Criterion) {
+    let v = get_test_vector_gaussian(1_000_000);
+
+    c.bench_function("quickhull 1000000", |b| b.iter(|| quickhull(&v)));
+}
+
+// below the threshold of the parallel recursion, this measures its overhead
+#[cfg(feature = "parallel")]
+fn bench_par_quickhull_2048(c: &mut Criterion) {
+    let v = get_test_vector_2d(2048);
+
+    c.bench_function("par_quickhull", |b| b.iter(|| par_quickhull(&v)));
+}
+
+#[cfg(feature = "parallel")]
+fn bench_par_quickhull_1000000(c: &mut Criterion) {
+    let v = get_test_vector_gaussian(1_000_000);
+
+    c.bench_function("par_quickhull 1000000", |b| b.iter(|| par_quickhull(&v)));
+}
+
 fn bench_jarvis_2048(c: &mut Criterion) {
     let v = get_test_vector_2d(2048);
 
@@ -50,25 +85,103 @@ fn bench_chan_akl_2048(c: &mut Criterion) {
     c.bench_function("chan+akl", |b| b.iter(|| chan(&akl(&v))));
 }
 
+fn bench_kirkpatrick_2048(c: &mut Criterion) {
+    let v = get_test_vector_2d(2048);
+
+    c.bench_function("kirkpatrick", |b| b.iter(|| kirkpatrick(&v)));
+}
+
+fn bench_kirkpatrick_akl_2048(c: &mut Criterion) {
+    let v = get_test_vector_2d(2048);
+
+    c.bench_function("kirkpatrick+akl", |b| b.iter(|| kirkpatrick(&akl(&v))));
+}
+
+// many points, but only few of them on the hull
+fn bench_chan_65536(c: &mut Criterion) {
+    let v = get_test_vector_2d(65536);
+
+    c.bench_function("chan 65536", |b| b.iter(|| chan(&v)));
+}
+
+fn bench_kirkpatrick_65536(c: &mut Criterion) {
+    let v = get_test_vector_2d(65536);
+
+    c.bench_function("kirkpatrick 65536", |b| b.iter(|| kirkpatrick(&v)));
+}
+
 fn bench_quickhull3d_2048(c: &mut Criterion) {
     let v = get_test_vector_3d(80);
 
     c.bench_function("quickhull 3d", |b| b.iter(|| quickhull3d(&v)));
 }
 
+fn bench_quickhull3d_1000000(c: &mut Criterion) {
+    let v = get_test_vector_3d(1_000_000);
+
+    c.bench_function("quickhull 3d 1000000", |b| b.iter(|| quickhull3d(&v)));
+}
+
+fn bench_contains_1000000(c: &mut Criterion) {
+    let polygon = ConvexPolygon::from_points(&from_flat(&get_test_vector_gaussian(2048)));
+    let queries = from_flat(&get_test_vector_2d(1_000_000));
+
+    c.bench_function("contains 1000000", |b| b.iter(|| polygon.contains_all(&queries)));
+}
+
+fn bench_incremental_1000000(c: &mut Criterion) {
+    // a random walk with gaussian steps, the test vector is centred at (0.5, 0.5)
+    let walk: Vec<Point2> = from_flat(&get_test_vector_gaussian(1_000_000)).into_iter()
+        .scan(Point2::new(0., 0.), |p, step| {
+            *p += step - Point2::new(0.5, 0.5);
+            Some(*p)
+        })
+        .collect();
+
+    c.bench_function("incremental 1000000", |b| b.iter(|| {
+        let mut hull = IncrementalHull2d::new();
+        walk.iter().map(|&p| {
+            hull.insert(p);
+            hull.area()
+        }).sum::<f64>()
+    }));
+}
+
 criterion_group!{
     name = benches;
     config = Criterion::default();
     targets =
         bench_andrew_2048,
         bench_andrew_akl_2048,
+        bench_graham_2048,
+        bench_graham_akl_2048,
         bench_quickhull_2048,
         bench_quickhull_akl_2048,
+        bench_quickhull_1000000,
         bench_jarvis_2048,
         bench_jarvis_akl_2048,
         bench_chan_2048,
         bench_chan_akl_2048,
+        bench_kirkpatrick_2048,
+        bench_kirkpatrick_akl_2048,
+        bench_chan_65536,
+        bench_kirkpatrick_65536,
         bench_quickhull3d_2048,
+        bench_quickhull3d_1000000,
+        bench_contains_1000000,
+        bench_incremental_1000000,
+}
+
+#[cfg(feature = "parallel")]
+criterion_group!{
+    name = par_benches;
+    config = Criterion::default();
+    targets =
+        bench_par_quickhull_2048,
+        bench_par_quickhull_1000000,
 }
 
-criterion_main!(benches);
\ No newline at end of file
+#[cfg(not(feature = "parallel"))]
+criterion_main!(benches);
+#[cfg(feature = "parallel")]
+criterion_main!(benches, par_benches);
\ No newline at end of file
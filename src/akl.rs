@@ -1,26 +1,39 @@
-use itertools::Itertools;
-
+use crate::d2::{Point2, expect_flat, to_flat, gather};
 use crate::primitives::point_in_octagon;
 
+// points stores a contiguous array of 2N floats in the format x1, y1, x2, y2, ...
+// an odd number of values panics
 pub fn akl(pointset: &[f64]) -> Vec<f64> {
-    let start = (pointset[0], pointset[1]);
+    to_flat(&akl_points(expect_flat(pointset, "akl")))
+}
+
+pub fn akl_points<P: Into<Point2>>(pointset: impl IntoIterator<Item = P>) -> Vec<Point2> {
+    let pointset: Vec<Point2> = pointset.into_iter().map(Into::into).collect();
+    gather(&pointset, &akl_indices(&pointset))
+}
+
+/// indices of all points which are not discarded by the Akl-Toussaint heuristic
+pub fn akl_indices(pointset: &[Point2]) -> Vec<usize> {
+    if pointset.is_empty() {
+        return Vec::new()
+    }
+
+    let start = pointset[0];
     let octagon = pointset.iter()
-        .tuples::<(_, _)>()
-        .fold([start; 8], |[minx, minxmy, maxy, maxxpy, maxx, maxxmy, miny, minxpy], (&x, &y)| {
-            let minx = if x < minx.0 { (x, y) } else { minx };
-            let maxx = if x > maxx.0 { (x, y) } else { maxx };
-            let miny = if y < miny.1 { (x, y) } else { miny };
-            let maxy = if y > maxy.1 { (x, y) } else { maxy };
-            let minxpy = if x+y < minxpy.0 + minxpy.1 { (x, y) } else { minxpy };
-            let maxxpy = if x+y > maxxpy.0 + maxxpy.1 { (x, y) } else { maxxpy };
-            let minxmy = if x-y < minxmy.0 - minxmy.1 { (x, y) } else { minxmy };
-            let maxxmy = if x-y > maxxmy.0 - maxxmy.1 { (x, y) } else { maxxmy };
+        .fold([start; 8], |[minx, minxmy, maxy, maxxpy, maxx, maxxmy, miny, minxpy], &p| {
+            let (x, y) = (p.x, p.y);
+            let minx = if x < minx.x { p } else { minx };
+            let maxx = if x > maxx.x { p } else { maxx };
+            let miny = if y < miny.y { p } else { miny };
+            let maxy = if y > maxy.y { p } else { maxy };
+            let minxpy = if x+y < minxpy.x + minxpy.y { p } else { minxpy };
+            let maxxpy = if x+y > maxxpy.x + maxxpy.y { p } else { maxxpy };
+            let minxmy = if x-y < minxmy.x - minxmy.y { p } else { minxmy };
+            let maxxmy = if x-y > maxxmy.x - maxxmy.y { p } else { maxxmy };
             [minx, minxmy, maxy, maxxpy, maxx, maxxmy, miny, minxpy]
         });
 
-    pointset.iter()
-        .cloned()
-        .tuples::<(_, _)>()
-        .filter(|&p| !point_in_octagon(octagon, p))
-        .fold(Vec::new(), |mut acc, p| { acc.push(p.0); acc.push(p.1); acc })
+    (0..pointset.len())
+        .filter(|&i| !point_in_octagon(octagon, pointset[i]))
+        .collect()
 }
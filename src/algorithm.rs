@@ -0,0 +1,227 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::d2::{Point2, gather};
+use crate::error::{HullError, validate_2d};
+use crate::boundary::Boundary;
+use crate::{andrew_indices_with, graham_indices_with, jarvis_indices_with, chan_indices_with, kirkpatrick_indices_with, quickhull_indices_with};
+use crate::akl::akl_indices;
+
+/// common interface of all 2D hull algorithms, such that they can be
+/// chosen at runtime
+pub trait ConvexHull2d {
+    fn name(&self) -> String;
+
+    /// whether the runtime depends on the number of hull vertices `h`
+    fn output_sensitive(&self) -> bool;
+
+    /// indices of the hull vertices in counter-clockwise order, which
+    /// includes the points on the edges for `Boundary::All`
+    fn hull_indices_with(&self, pointset: &[Point2], boundary: Boundary) -> Vec<usize>;
+
+    /// indices of the hull vertices in counter-clockwise order
+    fn hull_indices(&self, pointset: &[Point2]) -> Vec<usize> {
+        self.hull_indices_with(pointset, Boundary::Extreme)
+    }
+
+    /// hull vertices in counter-clockwise order, which includes the points
+    /// on the edges for `Boundary::All`
+    fn hull_with(&self, pointset: &[Point2], boundary: Boundary) -> Vec<Point2> {
+        gather(pointset, &self.hull_indices_with(pointset, boundary))
+    }
+
+    /// hull vertices in counter-clockwise order
+    fn hull(&self, pointset: &[Point2]) -> Vec<Point2> {
+        self.hull_with(pointset, Boundary::Extreme)
+    }
+
+    /// like `hull_indices`, but fails for input which does not span a polygon
+    fn try_hull_indices(&self, pointset: &[Point2]) -> Result<Vec<usize>, HullError> {
+        validate_2d(pointset)?;
+        Ok(self.hull_indices(pointset))
+    }
+
+    /// like `hull`, but fails for input which does not span a polygon
+    fn try_hull(&self, pointset: &[Point2]) -> Result<Vec<Point2>, HullError> {
+        validate_2d(pointset)?;
+        Ok(self.hull(pointset))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Andrew;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Graham;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Jarvis;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Chan;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Kirkpatrick;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quickhull;
+
+/// discards points inside the Akl-Toussaint octagon before running the wrapped algorithm
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Akl<A>(pub A);
+
+impl ConvexHull2d for Andrew {
+    fn name(&self) -> String { "andrew".to_string() }
+    fn output_sensitive(&self) -> bool { false }
+    fn hull_indices_with(&self, pointset: &[Point2], boundary: Boundary) -> Vec<usize> { andrew_indices_with(pointset, boundary) }
+}
+
+impl ConvexHull2d for Graham {
+    fn name(&self) -> String { "graham".to_string() }
+    fn output_sensitive(&self) -> bool { false }
+    fn hull_indices_with(&self, pointset: &[Point2], boundary: Boundary) -> Vec<usize> { graham_indices_with(pointset, boundary) }
+}
+
+impl ConvexHull2d for Jarvis {
+    fn name(&self) -> String { "jarvis".to_string() }
+    fn output_sensitive(&self) -> bool { true }
+    fn hull_indices_with(&self, pointset: &[Point2], boundary: Boundary) -> Vec<usize> { jarvis_indices_with(pointset, boundary) }
+}
+
+impl ConvexHull2d for Chan {
+    fn name(&self) -> String { "chan".to_string() }
+    fn output_sensitive(&self) -> bool { true }
+    fn hull_indices_with(&self, pointset: &[Point2], boundary: Boundary) -> Vec<usize> { chan_indices_with(pointset, boundary) }
+}
+
+impl ConvexHull2d for Kirkpatrick {
+    fn name(&self) -> String { "kirkpatrick".to_string() }
+    fn output_sensitive(&self) -> bool { true }
+    fn hull_indices_with(&self, pointset: &[Point2], boundary: Boundary) -> Vec<usize> { kirkpatrick_indices_with(pointset, boundary) }
+}
+
+impl ConvexHull2d for Quickhull {
+    fn name(&self) -> String { "quickhull".to_string() }
+    fn output_sensitive(&self) -> bool { false }
+    fn hull_indices_with(&self, pointset: &[Point2], boundary: Boundary) -> Vec<usize> { quickhull_indices_with(pointset, boundary) }
+}
+
+impl<A: ConvexHull2d> ConvexHull2d for Akl<A> {
+    fn name(&self) -> String {
+        format!("{}+akl", self.0.name())
+    }
+
+    fn output_sensitive(&self) -> bool {
+        self.0.output_sensitive()
+    }
+
+    fn hull_indices_with(&self, pointset: &[Point2], boundary: Boundary) -> Vec<usize> {
+        // points on the boundary of the hull are never strictly inside the octagon
+        let remaining = akl_indices(pointset);
+        self.0.hull_indices_with(&gather(pointset, &remaining), boundary)
+            .into_iter()
+            .map(|i| remaining[i])
+            .collect()
+    }
+}
+
+/// runtime selection of an algorithm, e.g., from a configuration file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    Andrew,
+    Graham,
+    Jarvis,
+    Chan,
+    Kirkpatrick,
+    Quickhull,
+}
+
+impl Algorithm {
+    pub const ALL: [Algorithm; 6] = [
+        Algorithm::Andrew,
+        Algorithm::Graham,
+        Algorithm::Jarvis,
+        Algorithm::Chan,
+        Algorithm::Kirkpatrick,
+        Algorithm::Quickhull,
+    ];
+
+    fn get(&self) -> &dyn ConvexHull2d {
+        match self {
+            Algorithm::Andrew => &Andrew,
+            Algorithm::Graham => &Graham,
+            Algorithm::Jarvis => &Jarvis,
+            Algorithm::Chan => &Chan,
+            Algorithm::Kirkpatrick => &Kirkpatrick,
+            Algorithm::Quickhull => &Quickhull,
+        }
+    }
+}
+
+impl ConvexHull2d for Algorithm {
+    fn name(&self) -> String { self.get().name() }
+    fn output_sensitive(&self) -> bool { self.get().output_sensitive() }
+    fn hull_indices_with(&self, pointset: &[Point2], boundary: Boundary) -> Vec<usize> {
+        self.get().hull_indices_with(pointset, boundary)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownAlgorithm(pub String);
+
+impl fmt::Display for UnknownAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown algorithm '{}'", self.0)
+    }
+}
+
+impl std::error::Error for UnknownAlgorithm {}
+
+impl FromStr for Algorithm {
+    type Err = UnknownAlgorithm;
+
+    fn from_str(s: &str) -> Result<Algorithm, UnknownAlgorithm> {
+        Algorithm::ALL.iter()
+            .find(|a| a.name().eq_ignore_ascii_case(s))
+            .cloned()
+            .ok_or_else(|| UnknownAlgorithm(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::d2::{from_flat, to_flat};
+    use crate::util::get_test_vector_2d;
+    use crate::{andrew, graham, jarvis, chan, kirkpatrick, quickhull};
+
+    #[test]
+    fn parse_algorithm() {
+        for a in Algorithm::ALL {
+            assert_eq!(a.name().parse::<Algorithm>(), Ok(a));
+        }
+        assert_eq!("Quickhull".parse::<Algorithm>(), Ok(Algorithm::Quickhull));
+        assert!("bogosort".parse::<Algorithm>().is_err());
+    }
+
+    #[test]
+    fn try_hull_degenerate() {
+        let collinear: Vec<Point2> = (0..10).map(|i| Point2::new(i as f64, 2. * i as f64)).collect();
+        for a in Algorithm::ALL {
+            assert_eq!(a.try_hull(&collinear), Err(HullError::AllCollinear));
+            assert_eq!(Akl(a).try_hull(&collinear[..2]), Err(HullError::TooFewPoints { found: 2, required: 3 }));
+        }
+    }
+
+    #[test]
+    fn flat_wrappers_agree() {
+        let v = get_test_vector_2d(256);
+        let points = from_flat(&v);
+        let flat = [andrew, graham, jarvis, chan, kirkpatrick, quickhull];
+
+        for (algo, f) in Algorithm::ALL.iter().zip(flat) {
+            assert_eq!(to_flat(&algo.hull(&points)), f(&v));
+            assert_eq!(to_flat(&Akl(*algo).hull(&points)), f(&crate::akl(&v)));
+        }
+    }
+}
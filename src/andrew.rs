@@ -1,56 +1,92 @@
-use itertools::Itertools;
 use std::cmp::Ordering::{Less, Equal};
 
-use crate::primitives::cross2d;
+use crate::d2::{Point2, expect_flat, try_from_flat, to_flat, gather};
+use crate::error::{HullError, validate_2d};
+use crate::predicates::orient2d;
+use crate::boundary::Boundary;
+use crate::primitives::all_collinear;
 
 // points stores a contiguous array of 2N floats in the format x1, y1, x2, y2, ...
-#[cfg(not(feature = "visual"))]
+// an odd number of values panics
 pub fn andrew(pointset: &[f64]) -> Vec<f64> {
-    if pointset.len() < 3*2 {
-        return pointset.to_vec()
-    }
+    to_flat(&andrew_points(expect_flat(pointset, "andrew")))
+}
 
-    // sort by x coordinates
-    let mut sorted: Vec<(f64, f64)> = pointset.iter()
-        .cloned()
-        .tuples::<(_, _)>()
-        .collect();
+// points stores a contiguous array of 2N floats in the format x1, y1, x2, y2, ...
+pub fn try_andrew(pointset: &[f64]) -> Result<Vec<f64>, HullError> {
+    let pointset = try_from_flat(pointset)?;
+    validate_2d(&pointset)?;
+    Ok(to_flat(&gather(&pointset, &hull(&pointset, Boundary::Extreme))))
+}
+
+pub fn andrew_points<P: Into<Point2>>(pointset: impl IntoIterator<Item = P>) -> Vec<Point2> {
+    let pointset: Vec<Point2> = pointset.into_iter().map(Into::into).collect();
+    gather(&pointset, &hull(&pointset, Boundary::Extreme))
+}
+
+/// indices of the hull vertices in counter-clockwise order
+pub fn andrew_indices(pointset: &[Point2]) -> Vec<usize> {
+    hull(pointset, Boundary::Extreme)
+}
+
+/// like `andrew_indices`, but with `Boundary::All` the points on the edges are kept
+pub fn andrew_indices_with(pointset: &[Point2], boundary: Boundary) -> Vec<usize> {
+    hull(pointset, boundary)
+}
+
+fn sort_lexicographic(pointset: &[Point2]) -> Vec<usize> {
+    let mut sorted: Vec<usize> = (0..pointset.len()).collect();
 
     // sort by x and on tie by y
-    sorted.sort_unstable_by(|a, b| {
-            let tmp = a.0.partial_cmp(&b.0).unwrap_or(Less);
+    sorted.sort_unstable_by(|&a, &b| {
+            let (a, b) = (pointset[a], pointset[b]);
+            let tmp = a.x.partial_cmp(&b.x).unwrap_or(Less);
             if tmp != Equal {
                 tmp
             } else {
-                a.1.partial_cmp(&b.1).unwrap_or(Less)
+                a.y.partial_cmp(&b.y).unwrap_or(Less)
             }
         });
 
-    let mut hull = Vec::new();
+    // duplicates would be kept as collinear points
+    sorted.dedup_by(|a, b| pointset[*a] == pointset[*b]);
+
+    sorted
+}
+
+#[cfg(not(feature = "visual"))]
+fn hull(pointset: &[Point2], boundary: Boundary) -> Vec<usize> {
+    if pointset.len() < 3 {
+        return (0..pointset.len()).collect()
+    }
+
+    let sorted = sort_lexicographic(pointset);
+    // the chains would contain collinear points twice, so they are returned sorted instead
+    if sorted.len() < 3 || boundary == Boundary::All && all_collinear(pointset, &sorted) {
+        return sorted
+    }
+
+    let mut hull: Vec<usize> = Vec::new();
     let mut k = 0;
-    for i in sorted.iter() {
-        while k >= 4 && cross2d((hull[k-4], hull[k-3]), (hull[k-2], hull[k-1]), *i) <= 0f64 {
-            hull.pop();
+    for &i in sorted.iter() {
+        while k >= 2 && boundary.drops(orient2d(pointset[hull[k-2]], pointset[hull[k-1]], pointset[i])) {
             hull.pop();
-            k -= 2;
+            k -= 1;
         }
-        hull.push(i.0);
-        hull.push(i.1);
-        k += 2;
+        hull.push(i);
+        k += 1;
     }
-    let t = k+2;
-    for i in sorted.iter().rev() {
-        while k >= t && cross2d((hull[k-4], hull[k-3]), (hull[k-2], hull[k-1]), *i) <= 0f64 {
-            hull.pop();
+    let t = k+1;
+    // the last point is already the start of the upper chain
+    for &i in sorted.iter().rev().skip(1) {
+        while k >= t && boundary.drops(orient2d(pointset[hull[k-2]], pointset[hull[k-1]], pointset[i])) {
             hull.pop();
-            k -= 2;
+            k -= 1;
         }
-        hull.push(i.0);
-        hull.push(i.1);
-        k += 2;
+        hull.push(i);
+        k += 1;
     }
-    // -2 because first and last are same
-    hull.pop();
+    // -1 because first and last are same
     hull.pop();
 
     hull
@@ -59,82 +95,67 @@ pub fn andrew(pointset: &[f64]) -> Vec<f64> {
 #[cfg(feature = "visual")]
 use crate::visualization::SVG;
 
-// points stores a contiguous array of 2N floats in the format x1, y1, x2, y2, ...
 #[cfg(feature = "visual")]
-pub fn andrew(pointset: &[f64]) -> Vec<f64> {
-    if pointset.len() < 3*2 {
-        return pointset.to_vec()
+fn hull(pointset: &[Point2], boundary: Boundary) -> Vec<usize> {
+    if pointset.len() < 3 {
+        return (0..pointset.len()).collect()
     }
 
-    // sort by x coordinates
-    let mut sorted: Vec<(f64, f64)> = pointset.iter()
-        .cloned()
-        .tuples::<(_, _)>()
-        .collect();
-
-    // sort by x and on tie by y
-    sorted.sort_unstable_by(|a, b| {
-            let tmp = a.0.partial_cmp(&b.0).unwrap_or(Less);
-            if tmp != Equal {
-                tmp
-            } else {
-                a.1.partial_cmp(&b.1).unwrap_or(Less)
-            }
-        });
+    let sorted = sort_lexicographic(pointset);
+    // the chains would contain collinear points twice, so they are returned sorted instead
+    if sorted.len() < 3 || boundary == Boundary::All && all_collinear(pointset, &sorted) {
+        return sorted
+    }
 
     let mut g = 0;
 
-    let mut hull = Vec::new();
+    let mut hull: Vec<usize> = Vec::new();
     let mut k = 0;
-    for i in sorted.iter() {
-        while k >= 4 && cross2d((hull[k-4], hull[k-3]), (hull[k-2], hull[k-1]), *i) <= 0f64 {
-            hull.pop();
+    for &i in sorted.iter() {
+        while k >= 2 && boundary.drops(orient2d(pointset[hull[k-2]], pointset[hull[k-1]], pointset[i])) {
             hull.pop();
-            k -= 2;
+            k -= 1;
         }
-        hull.push(i.0);
-        hull.push(i.1);
-        k += 2;
+        hull.push(i);
+        k += 1;
 
         g += 1;
         let filename = format!("img/andrew_{:04}.svg", g);
         let mut s = SVG::new();
         s.points(pointset, "grey");
-        s.points(&hull, "black");
-        s.lines(&hull, "black");
-        s.points(&[i.0, i.1], "red");
+        s.points(&gather(pointset, &hull), "black");
+        s.lines(&gather(pointset, &hull), "black");
+        s.points(&[pointset[i]], "red");
         s.save(&filename).expect("io error");
     }
-    let t = k+2;
-    for i in sorted.iter().rev() {
-        while k >= t && cross2d((hull[k-4], hull[k-3]), (hull[k-2], hull[k-1]), *i) <= 0f64 {
-            hull.pop();
+    let t = k+1;
+    // the last point is already the start of the upper chain
+    for &i in sorted.iter().rev().skip(1) {
+        while k >= t && boundary.drops(orient2d(pointset[hull[k-2]], pointset[hull[k-1]], pointset[i])) {
             hull.pop();
-            k -= 2;
+            k -= 1;
         }
-        hull.push(i.0);
-        hull.push(i.1);
-        k += 2;
+        hull.push(i);
+        k += 1;
 
         g += 1;
         let filename = format!("img/andrew_{:04}.svg", g);
         let mut s = SVG::new();
         s.points(pointset, "grey");
-        s.points(&hull, "black");
-        s.lines(&hull, "black");
-        s.points(&[i.0, i.1], "red");
+        s.points(&gather(pointset, &hull), "black");
+        s.lines(&gather(pointset, &hull), "black");
+        s.points(&[pointset[i]], "red");
         s.save(&filename).expect("io error");
     }
-    // -2 because first and last are same
-    hull.pop();
+    // -1 because first and last are same
     hull.pop();
 
     g += 1;
     let filename = format!("img/andrew_{:04}.svg", g);
     let mut s = SVG::new();
     s.points(pointset, "grey");
-    s.points(&hull, "black");
-    s.polygon(&hull, "black");
+    s.points(&gather(pointset, &hull), "black");
+    s.polygon(&gather(pointset, &hull), "black");
     s.save(&filename).expect("io error");
 
     hull
@@ -143,25 +164,55 @@ pub fn andrew(pointset: &[f64]) -> Vec<f64> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{util::tests::{check_2048, check_square}, akl};
+    use crate::{util::tests::{check_2048, check_square, check_square_indices, check_square_boundary, get_square}};
+    use crate::{Andrew, Akl};
+    use crate::d2::from_flat;
 
     #[test]
     fn andrew_square() {
-        check_square(andrew);
+        check_square(&Andrew);
     }
 
     #[test]
     fn andrew_2048() {
-        check_2048(andrew, "andrew");
+        check_2048(&Andrew);
     }
 
     #[test]
     fn andrew_akl_square() {
-        check_square(|v| andrew(&akl(v)));
+        check_square(&Akl(Andrew));
     }
 
     #[test]
     fn andrew_akl_2048() {
-        check_2048(|v| andrew(&akl(v)), "andrew_akl");
+        check_2048(&Akl(Andrew));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn andrew_points_tuples() {
+        let v: Vec<(f64, f64)> = from_flat(&get_square())
+            .into_iter()
+            .map(Into::into)
+            .collect();
+
+        assert_eq!(andrew_points(v), andrew_points(from_flat(&get_square()).iter()));
+    }
+
+    #[test]
+    fn try_andrew_invalid() {
+        assert_eq!(try_andrew(&get_square()[1..]), Err(HullError::OddLength { len: 11 }));
+        assert_eq!(try_andrew(&[0., 0., 1., f64::NAN, 0., 1.]), Err(HullError::NonFinite { index: 1 }));
+        assert_eq!(try_andrew(&get_square()).map(|h| h.len()), Ok(8));
+    }
+
+    #[test]
+    fn andrew_square_boundary() {
+        check_square_boundary(&Andrew);
+        check_square_boundary(&Akl(Andrew));
+    }
+
+    #[test]
+    fn andrew_indices_square() {
+        check_square_indices(andrew_indices);
+    }
+}
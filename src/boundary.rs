@@ -0,0 +1,118 @@
+use crate::d2::Point2;
+use crate::predicates::{orient2d, cmp_dist2};
+
+/// which points on the boundary of the hull are part of the output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Boundary {
+    /// only the extreme points, i.e., the corners of the hull
+    #[default]
+    Extreme,
+    /// every input point on an edge of the hull (or on a facet in 3D)
+    All,
+}
+
+impl Boundary {
+    /// whether the middle point of a turn with orientation `o` is not part of the hull
+    pub(crate) fn drops(self, o: f64) -> bool {
+        match self {
+            Boundary::Extreme => o <= 0f64,
+            Boundary::All => o < 0f64,
+        }
+    }
+}
+
+/// index of the edge `(hull[j], hull[j+1])` on which `p` lies, if any
+///
+/// `hull` must be a counter-clockwise polygon of at least three extreme
+/// points, which contains `p`
+fn edge_of(p: Point2, hull: &[usize], pointset: &[Point2]) -> Option<usize> {
+    let v = |j: usize| pointset[hull[j]];
+    let h = hull.len();
+
+    if orient2d(v(0), v(1), p) == 0. {
+        return Some(0)
+    }
+    if orient2d(v(h-1), v(0), p) == 0. {
+        return Some(h-1)
+    }
+
+    // binary search for the wedge at v(0) which contains p
+    let mut lo = 1;
+    let mut hi = h-1;
+    while hi - lo > 1 {
+        let mid = (lo + hi) / 2;
+        if orient2d(v(0), v(mid), p) >= 0. {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+
+    if orient2d(v(lo), v(hi), p) == 0. {
+        Some(lo)
+    } else {
+        None
+    }
+}
+
+/// inserts all points lying on the edges of the convex polygon `hull`,
+/// which consists only of extreme points, in O(n log h)
+pub(crate) fn insert_boundary_points(pointset: &[Point2], hull: Vec<usize>) -> Vec<usize> {
+    let h = hull.len();
+    if h < 2 {
+        return hull
+    }
+
+    // a segment has only one edge, the way back would repeat all points
+    let edges = if h == 2 { 1 } else { h };
+    let mut on_edge: Vec<Vec<usize>> = vec![Vec::new(); edges];
+
+    for (i, &p) in pointset.iter().enumerate() {
+        let j = if h == 2 {
+            if orient2d(pointset[hull[0]], pointset[hull[1]], p) == 0. { Some(0) } else { None }
+        } else {
+            edge_of(p, &hull, pointset)
+        };
+
+        if let Some(j) = j {
+            let (a, b) = (pointset[hull[j]], pointset[hull[(j+1) % h]]);
+            if p != a && p != b {
+                on_edge[j].push(i);
+            }
+        }
+    }
+
+    let mut out = Vec::with_capacity(pointset.len());
+    for (j, mut between) in on_edge.into_iter().enumerate() {
+        let a = pointset[hull[j]];
+        between.sort_by(|&p, &q| cmp_dist2(a, pointset[p], pointset[q]));
+        between.dedup_by(|p, q| pointset[*p] == pointset[*q]);
+
+        out.push(hull[j]);
+        out.extend(between);
+    }
+    out.extend(hull.into_iter().skip(edges));
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boundary_points() {
+        let p = Point2::new;
+        let v = vec![
+            p(0., 0.), p(2., 0.), p(2., 2.), p(0., 2.),
+            p(1., 0.), p(0.5, 0.), p(2., 1.), p(0., 1.5), p(0., 1.5),
+            p(1., 1.), p(1., 2. - 1e-15),
+        ];
+
+        let hull = insert_boundary_points(&v, vec![0, 1, 2, 3]);
+        assert_eq!(hull, vec![0, 5, 4, 1, 6, 2, 3, 7]);
+
+        let segment = insert_boundary_points(&v, vec![0, 1]);
+        assert_eq!(segment, vec![0, 5, 4, 1]);
+    }
+}
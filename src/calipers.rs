@@ -0,0 +1,376 @@
+use crate::d2::Point2;
+use crate::primitives::dist2;
+
+// Shamos, "Computational Geometry", PhD thesis, Yale University (1978).
+//
+// Two parallel lines support the hull at the vertices `i` and `j` and are
+// rotated counter-clockwise, until one of them lies flush with the next
+// edge, whose end becomes the new support vertex. After half a turn every
+// antipodal pair has been visited, which takes O(h) steps.
+
+/// iterator over all antipodal vertex pairs of a convex polygon, see `antipodal_pairs`
+#[derive(Debug, Clone)]
+pub struct AntipodalPairs<'a> {
+    hull: &'a [Point2],
+    i: usize,
+    j: usize,
+    /// steps left for `i` and `j` until half a turn is completed
+    left_i: usize,
+    left_j: usize,
+    first: bool,
+    pending: Vec<(usize, usize)>,
+}
+
+/// all pairs of vertices of the counter-clockwise polygon `hull`, which
+/// admit two parallel supporting lines, in O(h)
+///
+/// the pairs are indices into `hull`, the farthest pair is one of them
+pub fn antipodal_pairs(hull: &[Point2]) -> AntipodalPairs<'_> {
+    let h = hull.len();
+    let lowest = (0..h).fold(0, |min, k| {
+        if (hull[k].y, hull[k].x) < (hull[min].y, hull[min].x) { k } else { min }
+    });
+    let highest = (0..h).fold(0, |max, k| {
+        if (hull[k].y, hull[k].x) > (hull[max].y, hull[max].x) { k } else { max }
+    });
+
+    // a segment has a single pair and no edges to rotate over
+    let (left_i, left_j) = if h < 3 {
+        (0, 0)
+    } else {
+        ((highest + h - lowest) % h, (lowest + h - highest) % h)
+    };
+
+    AntipodalPairs {
+        hull,
+        i: lowest,
+        j: highest,
+        left_i,
+        left_j,
+        first: h >= 2,
+        pending: Vec::new(),
+    }
+}
+
+impl<'a> Iterator for AntipodalPairs<'a> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        if let Some(pair) = self.pending.pop() {
+            return Some(pair)
+        }
+        if self.first {
+            self.first = false;
+            return Some((self.i, self.j))
+        }
+        if self.left_i == 0 && self.left_j == 0 {
+            return None
+        }
+
+        let h = self.hull.len();
+        let (i, j) = (self.i, self.j);
+        let (ni, nj) = ((i + 1) % h, (j + 1) % h);
+
+        // the edge which turns less is reached first by its caliper
+        let turn = if self.left_i == 0 {
+            1.
+        } else if self.left_j == 0 {
+            -1.
+        } else {
+            (self.hull[ni] - self.hull[i]).cross(self.hull[nj] - self.hull[j])
+        };
+
+        let mut visited = Vec::with_capacity(3);
+        if turn < 0. {
+            self.i = ni;
+            self.left_i -= 1;
+        } else if turn > 0. {
+            self.j = nj;
+            self.left_j -= 1;
+        } else {
+            // both calipers are flush with parallel edges
+            visited.push((ni, j));
+            visited.push((i, nj));
+            self.i = ni;
+            self.j = nj;
+            self.left_i -= 1;
+            self.left_j -= 1;
+        }
+        // after half a turn the starting pair is reached again
+        if self.left_i > 0 || self.left_j > 0 {
+            visited.push((self.i, self.j));
+        }
+
+        self.pending.extend(visited.into_iter().rev());
+        self.pending.pop()
+    }
+}
+
+/// indices of the two vertices of `hull` farthest apart, in O(h)
+pub fn farthest_pair(hull: &[Point2]) -> Option<(usize, usize)> {
+    antipodal_pairs(hull)
+        .map(|(i, j)| (dist2(hull[i], hull[j]), (i, j)))
+        .fold(None, |max: Option<(f64, (usize, usize))>, candidate| match max {
+            Some(m) if m.0 >= candidate.0 => Some(m),
+            _ => Some(candidate),
+        })
+        .map(|(_, pair)| pair)
+}
+
+/// largest distance between two points of the counter-clockwise polygon `hull`
+pub fn diameter(hull: &[Point2]) -> f64 {
+    match farthest_pair(hull) {
+        Some((i, j)) => dist2(hull[i], hull[j]).sqrt(),
+        None => 0.,
+    }
+}
+
+/// the narrowest strip enclosing a convex polygon, which is bounded by the
+/// line through an edge and the parallel line through the farthest vertex
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Width {
+    pub width: f64,
+    /// indices of the end points of the edge into the hull
+    pub edge: (usize, usize),
+    /// index of the vertex on the opposite supporting line
+    pub vertex: usize,
+}
+
+impl Width {
+    /// both supporting lines as a point on the line and the common direction
+    pub fn supporting_lines(&self, hull: &[Point2]) -> [(Point2, Point2); 2] {
+        let direction = hull[self.edge.1] - hull[self.edge.0];
+        [(hull[self.edge.0], direction), (hull[self.vertex], direction)]
+    }
+}
+
+/// minimal width of the counter-clockwise polygon `hull` in O(h)
+///
+/// the width is attained perpendicular to an edge, the farthest vertex from
+/// the edges moves monotonically around the polygon
+pub fn min_width(hull: &[Point2]) -> Option<Width> {
+    let h = hull.len();
+    if h < 3 {
+        return None
+    }
+
+    // start at the farthest vertex from the first edge, since collinear
+    // points next to the edge would stop the search
+    let height = |i: usize, k: usize| (hull[(i + 1) % h] - hull[i]).cross(hull[k] - hull[i]);
+    let mut j = (0..h).fold(0, |max, k| if height(0, k) > height(0, max) { k } else { max });
+
+    let mut best: Option<Width> = None;
+    for i in 0..h {
+        let (a, b) = (hull[i], hull[(i + 1) % h]);
+        if a == b {
+            continue
+        }
+        let height = |k: usize| height(i, k);
+        while height((j + 1) % h) > height(j) {
+            j = (j + 1) % h;
+        }
+
+        let width = height(j) / (b - a).length();
+        if best.is_none_or(|w| width < w.width) {
+            best = Some(Width { width, edge: (i, (i + 1) % h), vertex: j });
+        }
+    }
+
+    best
+}
+
+/// rectangle enclosing a polygon, with one side flush with an edge of it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rectangle {
+    /// corners in counter-clockwise order, the first side lies on the edge
+    pub corners: [Point2; 4],
+    /// angle of the first side against the x-axis in radians
+    pub angle: f64,
+}
+
+impl Rectangle {
+    pub fn area(&self) -> f64 {
+        let [a, b, _, d] = self.corners;
+        (b - a).length() * (d - a).length()
+    }
+
+    pub fn perimeter(&self) -> f64 {
+        let [a, b, _, d] = self.corners;
+        2. * ((b - a).length() + (d - a).length())
+    }
+}
+
+/// the enclosing rectangle of `hull` minimizing `cost(length, height)`
+///
+/// the optimal rectangle for area and perimeter has a side flush with an
+/// edge of the hull, for every edge the three other sides touch the extreme
+/// vertices along and perpendicular to it, which only move forward
+fn min_rectangle(hull: &[Point2], cost: impl Fn(f64, f64) -> f64) -> Option<Rectangle> {
+    let h = hull.len();
+    if h < 3 {
+        return None
+    }
+
+    let along = |i: usize, k: usize| (hull[(i + 1) % h] - hull[i]).dot(hull[k] - hull[i]);
+    let height = |i: usize, k: usize| (hull[(i + 1) % h] - hull[i]).cross(hull[k] - hull[i]);
+    let argmax = |f: &dyn Fn(usize) -> f64| (0..h).fold(0, |max, k| if f(k) > f(max) { k } else { max });
+    let mut right = argmax(&|k| along(0, k));
+    let mut top = argmax(&|k| height(0, k));
+    let mut left = argmax(&|k| -along(0, k));
+
+    let mut best: Option<(f64, Rectangle)> = None;
+    for i in 0..h {
+        let (a, b) = (hull[i], hull[(i + 1) % h]);
+        if a == b {
+            continue
+        }
+        while along(i, (right + 1) % h) > along(i, right) {
+            right = (right + 1) % h;
+        }
+        while height(i, (top + 1) % h) > height(i, top) {
+            top = (top + 1) % h;
+        }
+        while along(i, (left + 1) % h) < along(i, left) {
+            left = (left + 1) % h;
+        }
+
+        let length = (b - a).length();
+        let u = (b - a) / length;
+        let n = Point2::new(-u.y, u.x);
+        let (lo, hi) = (along(i, left) / length, along(i, right) / length);
+        let up = height(i, top) / length;
+
+        let c = cost(hi - lo, up);
+        if best.is_none_or(|(min, _)| c < min) {
+            let rectangle = Rectangle {
+                corners: [a + u * lo, a + u * hi, a + u * hi + n * up, a + u * lo + n * up],
+                angle: u.y.atan2(u.x),
+            };
+            best = Some((c, rectangle));
+        }
+    }
+
+    best.map(|(_, rectangle)| rectangle)
+}
+
+/// the rectangle of minimal area enclosing the counter-clockwise polygon `hull`, in O(h)
+pub fn min_area_rectangle(hull: &[Point2]) -> Option<Rectangle> {
+    min_rectangle(hull, |length, height| length * height)
+}
+
+/// the rectangle of minimal perimeter enclosing the counter-clockwise polygon `hull`, in O(h)
+pub fn min_perimeter_rectangle(hull: &[Point2]) -> Option<Rectangle> {
+    min_rectangle(hull, |length, height| length + height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+    use assert_approx_eq::assert_approx_eq;
+    use crate::d2::from_flat;
+    use crate::andrew_points;
+    use crate::util::{get_test_vector_2d, get_test_vector_gaussian};
+
+    #[test]
+    fn antipodal_square() {
+        let p = Point2::new;
+        let square = [p(0., 0.), p(1., 0.), p(1., 1.), p(0., 1.)];
+
+        let mut pairs: Vec<(usize, usize)> = antipodal_pairs(&square)
+            .map(|(i, j)| (i.min(j), i.max(j)))
+            .collect();
+        pairs.sort_unstable();
+        // all vertices face each other across parallel edges
+        assert_eq!(pairs, vec![(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)]);
+
+        let w = min_width(&square).unwrap();
+        assert_approx_eq!(w.width, 1.);
+        let [(a, d), (b, _)] = w.supporting_lines(&square);
+        assert_approx_eq!(d.cross(b - a).abs(), d.length());
+
+        // collinear points on the edges do not change anything
+        let boundary = [p(0., 0.), p(0.5, 0.), p(1., 0.), p(1., 0.5), p(1., 1.), p(0., 1.)];
+        assert_approx_eq!(min_width(&boundary).unwrap().width, 1.);
+        assert_approx_eq!(diameter(&boundary), 2f64.sqrt());
+
+        assert_eq!(antipodal_pairs(&square[..2]).collect::<Vec<_>>(), vec![(0, 1)]);
+        assert_eq!(antipodal_pairs(&square[..1]).count(), 0);
+    }
+
+    #[test]
+    fn calipers_brute_force() {
+        for v in [get_test_vector_2d(1000), get_test_vector_gaussian(1000)] {
+            let hull = andrew_points(from_flat(&v));
+            let h = hull.len();
+
+            let mut diameter2 = 0f64;
+            for a in &hull {
+                for b in &hull {
+                    diameter2 = diameter2.max(dist2(*a, *b));
+                }
+            }
+            assert_eq!(diameter(&hull), diameter2.sqrt());
+
+            let width = (0..h)
+                .map(|i| {
+                    let (a, b) = (hull[i], hull[(i + 1) % h]);
+                    hull.iter().map(|&p| (b - a).cross(p - a) / (b - a).length()).fold(0., f64::max)
+                })
+                .fold(f64::INFINITY, f64::min);
+            assert_eq!(min_width(&hull).unwrap().width, width);
+
+            // every pair is visited once, in at most h + #parallel edges steps
+            let pairs: Vec<_> = antipodal_pairs(&hull).collect();
+            assert!(pairs.len() >= h / 2 && pairs.len() <= 3 * h / 2);
+        }
+    }
+
+    #[test]
+    fn rectangles() {
+        let p = Point2::new;
+        // rotated rectangle of size 2 x 1 with an extra vertex
+        let rectangle = [p(0., 0.), p(2., 2.), p(1.5, 2.5), p(1., 3.), p(-1., 1.)];
+        for r in [min_area_rectangle(&rectangle).unwrap(), min_perimeter_rectangle(&rectangle).unwrap()] {
+            assert_approx_eq!(r.area(), 4.);
+            assert_approx_eq!(r.perimeter(), 6. * 2f64.sqrt());
+            assert_approx_eq!(r.angle.rem_euclid(PI / 2.), PI / 4.);
+        }
+        assert!(min_area_rectangle(&rectangle[..2]).is_none());
+    }
+
+    #[test]
+    fn rectangles_brute_force() {
+        let hull = andrew_points(from_flat(&get_test_vector_gaussian(1000)));
+        let area = min_area_rectangle(&hull).unwrap();
+        let perimeter = min_perimeter_rectangle(&hull).unwrap();
+        assert!(area.area() <= perimeter.area());
+        assert!(perimeter.perimeter() <= area.perimeter());
+
+        for r in [area, perimeter] {
+            // every vertex is inside, up to rounding
+            for q in &hull {
+                for k in 0..4 {
+                    let (a, b) = (r.corners[k], r.corners[(k + 1) % 4]);
+                    assert!((b - a).cross(*q - a) > -1e-9);
+                }
+            }
+        }
+
+        // rotation sweep over all edge directions in O(h^2)
+        let h = hull.len();
+        let mut min_area = f64::INFINITY;
+        let mut min_perimeter = f64::INFINITY;
+        for i in 0..h {
+            let u = hull[(i + 1) % h] - hull[i];
+            let u = u / u.length();
+            let along: Vec<f64> = hull.iter().map(|&q| u.dot(q)).collect();
+            let across: Vec<f64> = hull.iter().map(|&q| u.cross(q)).collect();
+            let extent = |v: &[f64]| v.iter().cloned().fold(f64::NEG_INFINITY, f64::max) - v.iter().cloned().fold(f64::INFINITY, f64::min);
+            min_area = min_area.min(extent(&along) * extent(&across));
+            min_perimeter = min_perimeter.min(2. * (extent(&along) + extent(&across)));
+        }
+        assert_approx_eq!(area.area(), min_area);
+        assert_approx_eq!(perimeter.perimeter(), min_perimeter);
+    }
+}
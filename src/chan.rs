@@ -1,76 +1,108 @@
 use std::iter;
-use itertools::Itertools;
+use std::cmp::Ordering::Less;
 
-use crate::primitives::{cross2d, tangent, dist2};
+use crate::d2::{Point2, expect_flat, try_from_flat, to_flat, gather};
+use crate::error::{HullError, validate_2d};
+use crate::boundary::{Boundary, insert_boundary_points};
+use crate::primitives::tangent;
+use crate::predicates::{orient2d, cmp_dist2};
 
-use super::andrew::andrew;
+use super::andrew::andrew_indices;
 
 // points stores a contiguous array of 2N floats in the format x1, y1, x2, y2, ...
-#[cfg(not(feature = "visual"))]
+// an odd number of values panics
 pub fn chan(pointset: &[f64]) -> Vec<f64> {
-    if pointset.len() < 3*2 {
-        return pointset.to_vec()
+    to_flat(&chan_points(expect_flat(pointset, "chan")))
+}
+
+// points stores a contiguous array of 2N floats in the format x1, y1, x2, y2, ...
+pub fn try_chan(pointset: &[f64]) -> Result<Vec<f64>, HullError> {
+    let pointset = try_from_flat(pointset)?;
+    validate_2d(&pointset)?;
+    Ok(to_flat(&gather(&pointset, &hull(&pointset))))
+}
+
+pub fn chan_points<P: Into<Point2>>(pointset: impl IntoIterator<Item = P>) -> Vec<Point2> {
+    let pointset: Vec<Point2> = pointset.into_iter().map(Into::into).collect();
+    gather(&pointset, &hull(&pointset))
+}
+
+/// indices of the hull vertices in counter-clockwise order
+pub fn chan_indices(pointset: &[Point2]) -> Vec<usize> {
+    hull(pointset)
+}
+
+/// like `chan_indices`, but with `Boundary::All` the points on the edges are kept
+pub fn chan_indices_with(pointset: &[Point2], boundary: Boundary) -> Vec<usize> {
+    match boundary {
+        Boundary::Extreme => hull(pointset),
+        Boundary::All => insert_boundary_points(pointset, hull(pointset)),
+    }
+}
+
+/// andrew hulls of the chunks of size `m`, made cyclic by repeating the first point
+fn subhulls(pointset: &[Point2], m: usize) -> Vec<Vec<usize>> {
+    pointset.chunks(m)
+        .enumerate()
+        .map(|(n, s)| {
+            let hull = andrew_indices(s);
+            let first = hull[0];
+            hull.into_iter()
+                .chain(iter::once(first))
+                .map(|i| i + n*m)
+                .collect()
+        })
+        .collect()
+}
+
+#[cfg(not(feature = "visual"))]
+fn hull(pointset: &[Point2]) -> Vec<usize> {
+    if pointset.len() < 3 {
+        return (0..pointset.len()).collect()
     }
 
     let mut m = 3;
 
     // starting conditions for Jarvis part can be precalculated
-    let start = (pointset[0], pointset[1]);
-    let min = pointset.iter()
-        .tuples::<(_, _)>()
-        .fold(start, |min, (&x, &y)| {
-            if x < min.0 { (x, y) } else { min }
+    let min = (0..pointset.len())
+        .fold(0, |min, i| {
+            // lexicographic, such that the start is a corner
+            if (pointset[i].x, pointset[i].y) < (pointset[min].x, pointset[min].y) { i } else { min }
         });
 
     loop {
         // split the pointset into k = n/m subsets of size <= m
-        let subsets: Vec<_> = pointset.chunks(m*2)
-            .collect();
         // calculate the subhulls with andrews
-        // also make them cyclic and partition into tuples
-        let mut subhulls: Vec<_> = subsets.iter()
-            .map(|s| {
-                let hull: Vec<_> = andrew(s);
-                let first = (hull[0], hull[1]);
-                hull.iter()
-                    .cloned()
-                    .tuples::<(_, _)>()
-                    .chain(iter::once(first))
-                    .collect()
-            })
-            .collect();
-
-        let mut hull = Vec::new();
-        hull.push(min.0);
-        hull.push(min.1);
+        // also make them cyclic
+        let mut subhulls = subhulls(pointset, m);
+
+        let mut hull = vec![min];
 
         let mut q = min;
 
         for _ in 0..m {
-            let mut all_t: Vec<f64> = Vec::new();
+            let mut all_t: Vec<usize> = Vec::new();
             for s in &mut subhulls {
-                let a = (hull[hull.len()-2], hull[hull.len()-1]);
-                let t = tangent(a, s);
+                let a = pointset[hull[hull.len()-1]];
+                let t = tangent(a, s, pointset);
 
-                all_t.push(t.0);
-                all_t.push(t.1);
+                all_t.push(t);
 
-                let orientation = cross2d(a, t, q);
+                let orientation = orient2d(a, pointset[t], pointset[q]);
                 if orientation > 0f64 {
                     q = t;
                 } else if orientation == 0f64 {
                     // take the one furthest away, to avoid collinear points
-                    if dist2(a, q) < dist2(a, t) {
+                    if cmp_dist2(a, pointset[q], pointset[t]) == Less {
                         q = t;
                     }
                 }
             }
 
-            if q == min {
+            if pointset[q] == pointset[min] {
                 return hull;
             }
-            hull.push(q.0);
-            hull.push(q.1);
+            hull.push(q);
         }
         m *= m;
         hull.clear();
@@ -80,75 +112,57 @@ pub fn chan(pointset: &[f64]) -> Vec<f64> {
 #[cfg(feature = "visual")]
 use crate::visualization::SVG;
 
-// points stores a contiguous array of 2N floats in the format x1, y1, x2, y2, ...
 #[cfg(feature = "visual")]
-pub fn chan(pointset: &[f64]) -> Vec<f64> {
-    if pointset.len() < 3*2 {
-        return pointset.to_vec()
+fn hull(pointset: &[Point2]) -> Vec<usize> {
+    if pointset.len() < 3 {
+        return (0..pointset.len()).collect()
     }
 
     let mut m = 3;
 
     // starting conditions for Jarvis part can be precalculated
-    let start = (pointset[0], pointset[1]);
-    let min = pointset.iter()
-        .tuples::<(_, _)>()
-        .fold(start, |min, (&x, &y)| {
-            if x < min.0 { (x, y) } else { min }
+    let min = (0..pointset.len())
+        .fold(0, |min, i| {
+            // lexicographic, such that the start is a corner
+            if (pointset[i].x, pointset[i].y) < (pointset[min].x, pointset[min].y) { i } else { min }
         });
 
     let mut g = 0;
 
     loop {
         // split the pointset into k = n/m subsets of size <= m
-        let subsets: Vec<_> = pointset.chunks(m*2)
-            .collect();
         // calculate the subhulls with andrews
-        // also make them cyclic and partition into tuples
-        let mut subhulls: Vec<Vec<_>> = subsets.iter()
-            .map(|s| {
-                let hull: Vec<_> = andrew(s);
-                let first = (hull[0], hull[1]);
-                hull.iter()
-                    .cloned()
-                    .tuples::<(_, _)>()
-                    .chain(iter::once(first))
-                    .collect()
-            })
-            .collect();
+        // also make them cyclic
+        let mut subhulls = subhulls(pointset, m);
 
         let filename = format!("img/chan_{:04}.svg", g);
         let mut s = SVG::new();
         s.points(pointset, "grey");
-        for h in subhulls.clone() {
-            let h: Vec<f64> = h.iter().flat_map(|tup| iter::once(tup.0).chain(iter::once(tup.1))).collect();
-            s.polygon(&h, "grey");
+        for h in subhulls.iter() {
+            s.polygon(&gather(pointset, h), "grey");
         }
         s.save(&filename).expect("io error");
 
         g += 1;
 
-        let mut hull = Vec::new();
-        hull.push(min.0);
-        hull.push(min.1);
+        let mut hull = vec![min];
 
         let mut q = min;
 
         for _ in 0..m {
-            let mut all_t: Vec<f64> = Vec::new();
+            let mut all_t: Vec<usize> = Vec::new();
             for s in &mut subhulls {
-                let a = (hull[hull.len()-2], hull[hull.len()-1]);
-                let t = tangent(a, s);
+                let a = pointset[hull[hull.len()-1]];
+                let t = tangent(a, s, pointset);
 
-                all_t.push(t.0);
-                all_t.push(t.1);
+                all_t.push(t);
 
-                let orientation = cross2d(a, t, q);
+                let orientation = orient2d(a, pointset[t], pointset[q]);
                 if orientation > 0f64 {
                     q = t;
                 } else if orientation == 0f64 {
                     // take the one furthest away, to avoid collinear points
-                    if dist2(a, q) < dist2(a, t) {
+                    if cmp_dist2(a, pointset[q], pointset[t]) == Less {
                         q = t;
                     }
                 }
@@ -157,36 +171,33 @@ pub fn chan(pointset: &[f64]) -> Vec<f64> {
             let filename = format!("img/chan_{:04}.svg", g);
             let mut s = SVG::new();
             s.points(pointset, "grey");
-            for h in subhulls.clone() {
-                let h: Vec<f64> = h.iter().flat_map(|tup| iter::once(tup.0).chain(iter::once(tup.1))).collect();
-                s.polygon(&h, "grey");
+            for h in subhulls.iter() {
+                s.polygon(&gather(pointset, h), "grey");
             }
-            s.lines(&hull, "black");
-            for (&t1, &t2) in all_t.iter().clone().tuples::<(_, _)>() {
-                s.dashed_lines(&[hull[hull.len()-2], hull[hull.len()-1], t1, t2], "green");
+            s.lines(&gather(pointset, &hull), "black");
+            for &t in all_t.iter() {
+                s.dashed_lines(&[pointset[hull[hull.len()-1]], pointset[t]], "green");
             }
-            s.points(&all_t, "green");
-            s.points(&hull, "black");
-            s.points(&[q.0, q.1], "red");
+            s.points(&gather(pointset, &all_t), "green");
+            s.points(&gather(pointset, &hull), "black");
+            s.points(&[pointset[q]], "red");
             s.save(&filename).expect("io error");
             g += 1;
 
-            if q == min {
+            if pointset[q] == pointset[min] {
                 let filename = format!("img/chan_{:04}.svg", g);
                 let mut s = SVG::new();
                 s.points(pointset, "grey");
-                for h in subhulls.clone() {
-                    let h: Vec<f64> = h.iter().flat_map(|tup| iter::once(tup.0).chain(iter::once(tup.1))).collect();
-                    s.polygon(&h, "grey");
+                for h in subhulls.iter() {
+                    s.polygon(&gather(pointset, h), "grey");
                 }
-                s.polygon(&hull, "black");
-                s.points(&hull, "black");
+                s.polygon(&gather(pointset, &hull), "black");
+                s.points(&gather(pointset, &hull), "black");
                 s.save(&filename).expect("io error");
 
                 return hull;
             }
-            hull.push(q.0);
-            hull.push(q.1);
+            hull.push(q);
         }
         m *= m;
         hull.clear();
@@ -196,25 +207,48 @@ pub fn chan(pointset: &[f64]) -> Vec<f64> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{util::tests::{check_2048, check_square}, akl};
+    use crate::{util::tests::{check_2048, check_square, check_square_indices, check_square_boundary}};
+    use crate::{Chan, Akl};
 
     #[test]
     fn chan_square() {
-        check_square(chan);
+        check_square(&Chan);
     }
 
     #[test]
     fn chan_2048() {
-        check_2048(chan, "chan");
+        check_2048(&Chan);
     }
 
     #[test]
     fn chan_akl_square() {
-        check_square(|v| chan(&akl(v)));
+        check_square(&Akl(Chan));
     }
 
     #[test]
     fn chan_akl_2048() {
-        check_2048(|v| chan(&akl(v)), "chan_akl");
+        check_2048(&Akl(Chan));
+    }
+
+    #[test]
+    fn chan_collinear_subhulls() {
+        // subhulls degenerate to segments, on which the binary tangent search can not work
+        let mut v: Vec<Point2> = (0..20).map(|i| Point2::new(i as f64, i as f64)).collect();
+        v.push(Point2::new(0., 10.));
+        let hull = chan_points(&v);
+
+        assert_eq!(hull.len(), 3);
+        assert_eq!(try_chan(&[0., 0., 1., 1., 2., 2.]), Err(HullError::AllCollinear));
+    }
+
+    #[test]
+    fn chan_square_boundary() {
+        check_square_boundary(&Chan);
+        check_square_boundary(&Akl(Chan));
+    }
+
+    #[test]
+    fn chan_indices_square() {
+        check_square_indices(chan_indices);
     }
 }
\ No newline at end of file
@@ -0,0 +1,526 @@
+use std::cmp::Ordering::{self, Equal, Greater};
+use std::f64::consts::PI;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::d2::Point2;
+use crate::d3::{Point3, Facet3, centroid};
+use crate::mesh::HullMesh3;
+use crate::predicates::{orient2d, orient3d};
+use crate::primitives::{cross2d, dist2, perimeter_points};
+use crate::andrew::andrew_points;
+use crate::quickhull3d::quickhull3d;
+
+/// a convex polygon with fast queries, built from the extreme points of a
+/// 2D hull in counter-clockwise order, as returned by all 2D algorithms
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConvexPolygon {
+    pub vertices: Vec<Point2>,
+}
+
+impl ConvexPolygon {
+    /// `hull` has to consist of the extreme points only, i.e., constructed
+    /// with `Boundary::Extreme`
+    pub fn new(hull: Vec<Point2>) -> ConvexPolygon {
+        ConvexPolygon { vertices: hull }
+    }
+
+    /// the convex hull of `pointset`
+    pub fn from_points(pointset: &[Point2]) -> ConvexPolygon {
+        ConvexPolygon::new(andrew_points(pointset.iter().cloned()))
+    }
+
+    /// whether `p` is inside of the polygon or on its boundary, in O(log h)
+    ///
+    /// a binary search finds the wedge of the triangle fan around the first
+    /// vertex which contains `p`, which is then tested against the opposite edge
+    pub fn contains(&self, p: Point2) -> bool {
+        let v = &self.vertices;
+        let h = v.len();
+        match h {
+            0 => return false,
+            1 => return p == v[0],
+            2 => return orient2d(v[0], v[1], p) == 0.
+                && (p.x - v[0].x) * (p.x - v[1].x) <= 0.
+                && (p.y - v[0].y) * (p.y - v[1].y) <= 0.,
+            _ => {}
+        }
+
+        if orient2d(v[0], v[1], p) < 0. || orient2d(v[0], v[h-1], p) > 0. {
+            return false
+        }
+
+        let mut lo = 1;
+        let mut hi = h-1;
+        while hi - lo > 1 {
+            let mid = (lo + hi) / 2;
+            if orient2d(v[0], v[mid], p) >= 0. {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+
+        orient2d(v[lo], v[hi], p) >= 0.
+    }
+
+    /// index of the vertex farthest in `direction`, in O(log h)
+    ///
+    /// the projections onto `direction` rise and fall once around the
+    /// polygon, the binary search compares the slopes of the edges at the
+    /// ends and in the middle of the search interval
+    pub fn support(&self, direction: Point2) -> usize {
+        let v = &self.vertices;
+        let h = v.len();
+        let height = |i: usize| direction.dot(v[i % h]);
+        let up = |i: usize| height(i + 1) > height(i);
+        let linear = || (0..h).fold(0, |max, i| if height(i) > height(max) { i } else { max });
+
+        if h < 3 {
+            return linear()
+        }
+        if !up(0) && height(h-1) <= height(0) {
+            return 0
+        }
+
+        let (mut a, mut b) = (0, h);
+        loop {
+            let c = (a + b) / 2;
+            // only possible for degenerate polygons, e.g., with duplicate vertices
+            if c == a {
+                return linear()
+            }
+            let up_c = up(c);
+            if !up_c && height(c-1) <= height(c) {
+                return c
+            }
+
+            if up(a) {
+                if !up_c || height(a) > height(c) {
+                    b = c;
+                } else {
+                    a = c;
+                }
+            } else if up_c || height(a) >= height(c) {
+                a = c;
+            } else {
+                b = c;
+            }
+        }
+    }
+
+    /// distance from `p` to the boundary, negative inside of the polygon,
+    /// together with the closest point on the boundary, in O(h)
+    ///
+    /// # Panics
+    /// if the polygon has no vertices
+    pub fn signed_distance(&self, p: Point2) -> (f64, Point2) {
+        let v = &self.vertices;
+        let h = v.len();
+        assert!(h > 0, "distance to an empty polygon");
+
+        let inside = h >= 3 && self.contains(p);
+        let edges = if h == 1 { 1 } else { h };
+        let (d2, closest) = (0..edges)
+            .map(|i| {
+                let (a, b) = (v[i], v[(i+1) % h]);
+                let q = if inside {
+                    // the foot of the perpendicular of the nearest edge is on the edge
+                    let ab = b - a;
+                    p - Point2::new(-ab.y, ab.x) * (cross2d(a, b, p) / ab.dot(ab))
+                } else {
+                    closest_on_segment(a, b, p)
+                };
+                (dist2(p, q), q)
+            })
+            .fold((f64::INFINITY, p), |min, c| if c.0 < min.0 { c } else { min });
+
+        let d = d2.sqrt();
+        (if inside { -d } else { d }, closest)
+    }
+
+    /// width averaged over all directions, i.e., the integral of the support
+    /// function over the unit circle divided by pi
+    ///
+    /// by Cauchy's formula this is the perimeter divided by pi
+    pub fn mean_width(&self) -> f64 {
+        perimeter_points(&self.vertices) / PI
+    }
+
+    /// `contains` for every query point
+    pub fn contains_all(&self, points: &[Point2]) -> Vec<bool> {
+        points.iter().map(|&p| self.contains(p)).collect()
+    }
+
+    /// like `contains_all`, but distributed over all threads of the rayon pool
+    #[cfg(feature = "parallel")]
+    pub fn par_contains_all(&self, points: &[Point2]) -> Vec<bool> {
+        points.par_iter().map(|&p| self.contains(p)).collect()
+    }
+}
+
+/// the point of the segment from `a` to `b` closest to `p`
+fn closest_on_segment(a: Point2, b: Point2, p: Point2) -> Point2 {
+    let ab = b - a;
+    let length2 = ab.dot(ab);
+    if length2 == 0. {
+        return a
+    }
+    let t = ((p - a).dot(ab) / length2).clamp(0., 1.);
+    a + ab * t
+}
+
+/// a convex polytope with fast containment queries, built from the facets of a 3D hull
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConvexPolytope {
+    pub facets: Vec<Facet3>,
+    /// corners of the bounding box
+    min: Point3,
+    max: Point3,
+    /// the facets with exactly coplanar neighbours merged into convex polygons
+    mesh: HullMesh3,
+    /// for every face of `mesh` three of its corners spanning its plane,
+    /// counter-clockwise seen from outside
+    planes: Vec<[Point3; 3]>,
+    /// the centroid, `None` if it is not strictly inside due to rounding,
+    /// which happens only for extremely flat polytopes
+    centre: Option<Point3>,
+    /// the faces as points of the polar polytope around `centre`, i.e., their
+    /// normals divided by their distance to `centre`
+    polar: Vec<Point3>,
+    /// the faces hit by the rays from `centre` along +x, -x, +y, -y, +z, -z
+    starts: [usize; 6],
+}
+
+impl ConvexPolytope {
+    /// `facets` have to form a closed surface, counter-clockwise seen from outside
+    pub fn new(facets: Vec<Facet3>) -> ConvexPolytope {
+        let vertices = || facets.iter().flat_map(|f| f.vertices.iter());
+        let inf = f64::INFINITY;
+        let min = vertices().fold(Point3::new(inf, inf, inf), |m, p| Point3::new(m.x.min(p.x), m.y.min(p.y), m.z.min(p.z)));
+        let max = vertices().fold(Point3::new(-inf, -inf, -inf), |m, p| Point3::new(m.x.max(p.x), m.y.max(p.y), m.z.max(p.z)));
+
+        // distinct faces lie in distinct planes, such that the polar polytope
+        // has no ties between adjacent faces, which would stop the walk
+        let mesh = HullMesh3::from_facets(&facets).merge_coplanar(0.);
+        let planes: Vec<[Point3; 3]> = (0..mesh.num_faces())
+            .map(|f| {
+                // the largest triangle of the fan, since the corners of a face
+                // may be collinear
+                let v: Vec<Point3> = mesh.face_vertices(f).map(|i| mesh.vertices[i]).collect();
+                let area = |k: usize| (v[k] - v[0]).cross(v[k+1] - v[0]).length();
+                let k = (1..v.len()-1).fold(1, |max, k| if area(k) > area(max) { k } else { max });
+                [v[0], v[k], v[k+1]]
+            })
+            .collect();
+
+        let centroid = centroid(&facets);
+        let centre = planes.iter()
+            .all(|&[a, b, c]| orient3d(a, b, c, centroid) < 0.)
+            .then_some(centroid);
+        let polar = planes.iter()
+            .map(|&[a, b, c]| {
+                let n = (b - a).cross(c - a);
+                n / n.dot(a - centroid)
+            })
+            .collect();
+
+        let mut polytope = ConvexPolytope {
+            facets,
+            min,
+            max,
+            mesh,
+            planes,
+            centre,
+            polar,
+            starts: [0; 6],
+        };
+        let axes = [
+            Point3::new(1., 0., 0.), Point3::new(-1., 0., 0.),
+            Point3::new(0., 1., 0.), Point3::new(0., -1., 0.),
+            Point3::new(0., 0., 1.), Point3::new(0., 0., -1.),
+        ];
+        polytope.starts = axes.map(|d| polytope.climb(d, 0));
+        polytope
+    }
+
+    /// the convex hull of `pointset`
+    ///
+    /// # Panics
+    /// if the points do not span a volume
+    pub fn from_points(pointset: &[Point3]) -> ConvexPolytope {
+        ConvexPolytope::new(quickhull3d(pointset))
+    }
+
+    /// the support of the polar polytope in `direction`, which is the face hit
+    /// by the ray from `centre` along `direction` up to rounding, found by
+    /// hill climbing from `start`
+    fn climb(&self, direction: Point3, start: usize) -> usize {
+        let mut f = start;
+        let mut best = self.polar[f].dot(direction);
+        loop {
+            let next = self.mesh.face_neighbours(f)
+                .map(|g| (g, self.polar[g].dot(direction)))
+                .fold((f, best), |max, c| if c.1 > max.1 { c } else { max });
+            if next.0 == f {
+                return f
+            }
+            (f, best) = next;
+        }
+    }
+
+    /// the face hit by the ray from `centre` through `p`, verified exactly by
+    /// the cone from `centre` over the face, `None` if rounding misled the walk
+    fn exit(&self, p: Point3) -> Option<usize> {
+        let c = self.centre?;
+        let d = p - c;
+        // the start of the axis closest to `d`
+        let start = [d.x, -d.x, d.y, -d.y, d.z, -d.z].iter()
+            .zip(self.starts)
+            .fold((f64::NEG_INFINITY, 0), |max, (&x, f)| if x > max.0 { (x, f) } else { max }).1;
+
+        let f = self.climb(d, start);
+        let mesh = &self.mesh;
+        mesh.face_half_edges(f)
+            .all(|e| orient3d(c, mesh.vertices[mesh.half_edges[e].origin], mesh.vertices[mesh.target(e)], p) >= 0.)
+            .then_some(f)
+    }
+
+    /// whether `p` is outside of, on or inside of the boundary, in O(1) by
+    /// the plane of the exit face or by all planes as a fallback
+    fn side(&self, p: Point3) -> Ordering {
+        if p.x < self.min.x || p.y < self.min.y || p.z < self.min.z
+            || p.x > self.max.x || p.y > self.max.y || p.z > self.max.z {
+            return Greater
+        }
+        let orient = |f: usize| {
+            let [a, b, c] = self.planes[f];
+            // the exact sign, -0 is on the plane as well
+            orient3d(a, b, c, p).partial_cmp(&0.).unwrap_or(Greater)
+        };
+        match self.exit(p) {
+            Some(f) => orient(f),
+            None => (0..self.planes.len()).map(orient).max().unwrap_or(Greater),
+        }
+    }
+
+    /// whether `p` is inside of the polytope or on its boundary, evaluated exactly
+    ///
+    /// points outside of the bounding box are rejected immediately. For all
+    /// others the face hit by the ray from the centroid through `p` is found
+    /// by a walk over adjacent faces, `p` is inside, if it is not in front of
+    /// this face. The walk visits few faces of round polytopes, but O(F) in
+    /// the worst case
+    pub fn contains(&self, p: Point3) -> bool {
+        self.side(p) != Greater
+    }
+
+    /// whether `p` is on the boundary of the polytope, evaluated exactly like
+    /// `contains`
+    pub fn on_boundary(&self, p: Point3) -> bool {
+        self.side(p) == Equal
+    }
+
+    /// a vertex with maximal projection on `direction`, found by hill climbing
+    /// along the edges
+    pub fn support(&self, direction: Point3) -> Point3 {
+        self.mesh.vertices[self.mesh.support(direction)]
+    }
+
+    /// distance from `p` to the boundary, negative inside of the polytope,
+    /// together with the closest point on the boundary, in O(F)
+    pub fn signed_distance(&self, p: Point3) -> (f64, Point3) {
+        let inside = self.contains(p);
+        let (d2, closest) = self.facets.iter()
+            .map(|f| {
+                let q = f.closest_point(p);
+                let d = q - p;
+                (d.dot(d), q)
+            })
+            .fold((f64::INFINITY, p), |min, c| if c.0 < min.0 { c } else { min });
+
+        let d = d2.sqrt();
+        (if inside { -d } else { d }, closest)
+    }
+
+    /// `contains` for every query point
+    pub fn contains_all(&self, points: &[Point3]) -> Vec<bool> {
+        points.iter().map(|&p| self.contains(p)).collect()
+    }
+
+    /// like `contains_all`, but distributed over all threads of the rayon pool
+    #[cfg(feature = "parallel")]
+    pub fn par_contains_all(&self, points: &[Point3]) -> Vec<bool> {
+        points.par_iter().map(|&p| self.contains(p)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::d2::from_flat;
+    use crate::util::{get_test_vector_2d, get_test_vector_gaussian, get_test_vector_3d, get_test_vector_gaussian_3d};
+    use crate::util::tests::{get_square, get_cube, get_random_lattice_3d};
+    use crate::quickhull3d::try_quickhull3d;
+
+    #[test]
+    fn polygon_square() {
+        let p = Point2::new;
+        let square = ConvexPolygon::from_points(&from_flat(&get_square()));
+
+        assert!(square.contains(p(0.5, 0.5)));
+        // corners and edges are part of the polygon
+        assert!(square.contains(p(0., 0.)));
+        assert!(square.contains(p(1., 1.)));
+        assert!(square.contains(p(0.3, 0.)));
+        assert!(square.contains(p(0., 0.7)));
+        assert!(!square.contains(p(1.5, 0.)));
+        assert!(!square.contains(p(-0.5, 0.)));
+        assert!(!square.contains(p(0.5, 1. + 1e-15)));
+
+        let segment = ConvexPolygon::new(vec![p(0., 0.), p(2., 2.)]);
+        assert_eq!(segment.contains_all(&[p(1., 1.), p(3., 3.), p(1., 0.)]), vec![true, false, false]);
+    }
+
+    #[test]
+    fn polygon_brute_force() {
+        let polygon = ConvexPolygon::from_points(&from_flat(&get_test_vector_gaussian(1000)));
+        let queries = from_flat(&get_test_vector_2d(10000));
+        let h = polygon.vertices.len();
+
+        let expected: Vec<bool> = queries.iter()
+            .map(|&q| (0..h).all(|i| orient2d(polygon.vertices[i], polygon.vertices[(i+1) % h], q) >= 0.))
+            .collect();
+        assert_eq!(polygon.contains_all(&queries), expected);
+        assert!(expected.iter().any(|&x| x) && expected.iter().any(|&x| !x));
+        #[cfg(feature = "parallel")]
+        assert_eq!(polygon.par_contains_all(&queries), expected);
+    }
+
+    #[test]
+    fn polytope_lattice() {
+        let p = Point3::new;
+        let points = [p(-2., -2., -1.), p(-1., -2., 2.), p(1., -2., 1.), p(-2., -1., 1.), p(1., 1., -1.), p(-1., -2., 1.), p(1., -1., 2.)];
+        let polytope = ConvexPolytope::from_points(&points);
+        assert!(!polytope.contains(p(-1., -1., 2.)));
+
+        // every lattice point against the hulls of random lattice points
+        let queries: Vec<Point3> = (-3..=3)
+            .flat_map(|x| (-3..=3).flat_map(move |y| (-3..=3).map(move |z| p(x as f64, y as f64, z as f64))))
+            .collect();
+        for seed in 0..300 {
+            let points = get_random_lattice_3d(seed, 5 + seed as usize % 40, 2);
+            let Ok(hull) = try_quickhull3d(&points) else { continue };
+            let polytope = ConvexPolytope::new(hull.clone());
+            for &q in &queries {
+                let inside = hull.iter().all(|f| !f.visible_from(&q));
+                let on_plane = hull.iter().any(|f| orient3d(f.vertices[0], f.vertices[1], f.vertices[2], q) == 0.);
+                assert_eq!(polytope.contains(q), inside, "seed {} query {:?}", seed, q);
+                assert_eq!(polytope.on_boundary(q), inside && on_plane, "seed {} query {:?}", seed, q);
+            }
+        }
+    }
+
+    #[test]
+    fn polytope_cube() {
+        let p = Point3::new;
+        let cube = ConvexPolytope::from_points(&get_cube());
+
+        assert!(cube.contains(p(0.5, 0.5, 0.5)));
+        assert!(cube.contains(p(0.99, 0.01, 0.5)));
+        assert!(cube.contains(p(1., 1., 1.)));
+        assert!(cube.contains(p(0.5, 0., 0.5)));
+        assert!(!cube.contains(p(0.5, 1.5, 0.5)));
+        assert!(!cube.contains(p(0.5, -1e-16, 0.5)));
+        assert!(get_cube().into_iter().all(|q| cube.contains(q)));
+
+        assert!(cube.on_boundary(p(1., 1., 1.)));
+        assert!(cube.on_boundary(p(0.5, 0., 0.5)));
+        assert!(cube.on_boundary(p(0.3, 0.7, 1.)));
+        assert!(!cube.on_boundary(p(0.5, 0.5, 0.5)));
+        assert!(!cube.on_boundary(p(0.5, 1.5, 0.5)));
+    }
+
+    #[test]
+    fn polytope_brute_force() {
+        let hull = quickhull3d(&get_test_vector_gaussian_3d(1000));
+        let polytope = ConvexPolytope::new(hull.clone());
+        // shifted into the cloud around the origin
+        let mut queries: Vec<Point3> = get_test_vector_3d(10000).into_iter()
+            .map(|q| q - Point3::new(50., 50., 50.))
+            .collect();
+        // on the boundary and just inside or outside of it
+        for f in &hull {
+            for q in f.vertices.iter().cloned().chain([f.mid()]) {
+                queries.extend([q, q * (1. - 1e-15), q * (1. + 1e-15)]);
+            }
+        }
+
+        let expected: Vec<bool> = queries.iter()
+            .map(|q| hull.iter().all(|f| !f.visible_from(q)))
+            .collect();
+        assert_eq!(polytope.contains_all(&queries), expected);
+        assert!(expected.iter().any(|&x| x) && expected.iter().any(|&x| !x));
+        #[cfg(feature = "parallel")]
+        assert_eq!(polytope.par_contains_all(&queries), expected);
+    }
+
+    #[test]
+    fn polygon_support() {
+        let p = Point2::new;
+        let square = ConvexPolygon::from_points(&from_flat(&get_square()));
+        assert_eq!(square.vertices[square.support(p(1., 2.))], p(1., 1.));
+        assert_eq!(square.vertices[square.support(p(-1., -0.1))], p(0., 0.));
+        assert!((square.mean_width() - 4. / PI).abs() < 1e-12);
+
+        for v in [get_test_vector_gaussian(1000), get_test_vector_2d(100)] {
+            let polygon = ConvexPolygon::from_points(&from_flat(&v));
+            for k in 0..1000 {
+                let phi = k as f64 * 0.0123;
+                let d = p(phi.cos(), phi.sin());
+                let max = polygon.vertices.iter().map(|&q| d.dot(q)).fold(f64::NEG_INFINITY, f64::max);
+                assert_eq!(d.dot(polygon.vertices[polygon.support(d)]), max);
+            }
+            // axis aligned directions hit the parallel edges of the hull
+            for d in [p(1., 0.), p(0., 1.), p(-1., 0.), p(0., -1.)] {
+                let max = polygon.vertices.iter().map(|&q| d.dot(q)).fold(f64::NEG_INFINITY, f64::max);
+                assert_eq!(d.dot(polygon.vertices[polygon.support(d)]), max);
+            }
+        }
+    }
+
+    #[test]
+    fn polygon_distance() {
+        let p = Point2::new;
+        let square = ConvexPolygon::from_points(&from_flat(&get_square()));
+
+        assert_eq!(square.signed_distance(p(0.5, 0.25)), (-0.25, p(0.5, 0.)));
+        assert_eq!(square.signed_distance(p(0.5, 1.)), (0., p(0.5, 1.)));
+        assert_eq!(square.signed_distance(p(3., 0.5)), (2., p(1., 0.5)));
+        assert_eq!(square.signed_distance(p(4., 5.)), (5., p(1., 1.)));
+
+        let point = ConvexPolygon::new(vec![p(1., 1.)]);
+        assert_eq!(point.signed_distance(p(4., 5.)), (5., p(1., 1.)));
+        let segment = ConvexPolygon::new(vec![p(0., 0.), p(2., 0.)]);
+        assert_eq!(segment.signed_distance(p(1., -2.)), (2., p(1., 0.)));
+    }
+
+    #[test]
+    fn polytope_distance() {
+        let p = Point3::new;
+        let cube = ConvexPolytope::from_points(&get_cube());
+
+        let (d, q) = cube.signed_distance(p(0.5, 0.4, 0.2));
+        assert!((d + 0.2).abs() < 1e-12);
+        assert!((q - p(0.5, 0.4, 0.)).length() < 1e-12);
+        let (d, q) = cube.signed_distance(p(2., 2., 0.5));
+        assert!((d - 2f64.sqrt()).abs() < 1e-12);
+        assert!((q - p(1., 1., 0.5)).length() < 1e-12);
+        let (d, q) = cube.signed_distance(p(3., 3., 3.));
+        assert!((d - 12f64.sqrt()).abs() < 1e-12);
+        assert!((q - p(1., 1., 1.)).length() < 1e-12);
+        let (d, _) = cube.signed_distance(p(0.5, 0.5, 1.));
+        assert_eq!(d, 0.);
+    }
+}
@@ -0,0 +1,198 @@
+use std::ops::{Add, Sub, Mul, Div, Neg, AddAssign, SubAssign, MulAssign, DivAssign};
+
+use itertools::Itertools;
+
+use crate::error::HullError;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point2 {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point2 {
+    /// z-component of the cross product of `self` and `other`
+    pub fn cross(self, other: Point2) -> f64 {
+        self.x*other.y - self.y*other.x
+    }
+
+    pub fn dot(self, other: Point2) -> f64 {
+        self.x*other.x + self.y*other.y
+    }
+
+    pub fn length(self) -> f64 {
+        self.dot(self).sqrt()
+    }
+
+    pub fn new(x: f64, y: f64) -> Point2 {
+        Point2 {x, y}
+    }
+}
+
+impl Add for Point2 {
+    type Output = Point2;
+
+    fn add(self, other: Point2) -> Point2 {
+        Point2::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl Sub for Point2 {
+    type Output = Point2;
+
+    fn sub(self, other: Point2) -> Point2 {
+        Point2::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl Mul<f64> for Point2 {
+    type Output = Point2;
+
+    fn mul(self, factor: f64) -> Point2 {
+        Point2::new(self.x * factor, self.y * factor)
+    }
+}
+
+impl Div<f64> for Point2 {
+    type Output = Point2;
+
+    fn div(self, divisor: f64) -> Point2 {
+        Point2::new(self.x / divisor, self.y / divisor)
+    }
+}
+
+impl Neg for Point2 {
+    type Output = Point2;
+
+    fn neg(self) -> Point2 {
+        Point2::new(-self.x, -self.y)
+    }
+}
+
+impl AddAssign for Point2 {
+    fn add_assign(&mut self, other: Point2) {
+        self.x += other.x;
+        self.y += other.y;
+    }
+}
+
+impl SubAssign for Point2 {
+    fn sub_assign(&mut self, other: Point2) {
+        self.x -= other.x;
+        self.y -= other.y;
+    }
+}
+
+impl MulAssign<f64> for Point2 {
+    fn mul_assign(&mut self, factor: f64) {
+        self.x *= factor;
+        self.y *= factor;
+    }
+}
+
+impl DivAssign<f64> for Point2 {
+    fn div_assign(&mut self, divisor: f64) {
+        self.x /= divisor;
+        self.y /= divisor;
+    }
+}
+
+impl From<(f64, f64)> for Point2 {
+    fn from((x, y): (f64, f64)) -> Point2 {
+        Point2 {x, y}
+    }
+}
+
+impl From<[f64; 2]> for Point2 {
+    fn from([x, y]: [f64; 2]) -> Point2 {
+        Point2 {x, y}
+    }
+}
+
+impl From<&Point2> for Point2 {
+    fn from(p: &Point2) -> Point2 {
+        *p
+    }
+}
+
+impl From<Point2> for (f64, f64) {
+    fn from(p: Point2) -> (f64, f64) {
+        (p.x, p.y)
+    }
+}
+
+/// convert a contiguous array of 2N floats in the format x1, y1, x2, y2, ...
+/// into N points, a trailing odd value is ignored
+pub fn from_flat(coords: &[f64]) -> Vec<Point2> {
+    coords.iter()
+        .cloned()
+        .tuples::<(_, _)>()
+        .map(Point2::from)
+        .collect()
+}
+
+/// like `from_flat`, but rejects arrays of odd length
+pub fn try_from_flat(coords: &[f64]) -> Result<Vec<Point2>, HullError> {
+    if !coords.len().is_multiple_of(2) {
+        return Err(HullError::OddLength { len: coords.len() })
+    }
+    Ok(from_flat(coords))
+}
+
+/// like `try_from_flat`, but panics with the name of the `caller`, which
+/// guards the flat wrappers like `andrew` against silently dropping a value
+pub(crate) fn expect_flat(coords: &[f64], caller: &str) -> Vec<Point2> {
+    match try_from_flat(coords) {
+        Ok(points) => points,
+        Err(e) => panic!("{}: {}", caller, e),
+    }
+}
+
+/// convert points into a contiguous array in the format x1, y1, x2, y2, ...
+pub fn to_flat(points: &[Point2]) -> Vec<f64> {
+    points.iter()
+        .flat_map(|p| [p.x, p.y])
+        .collect()
+}
+
+/// the points at the given indices, e.g., to resolve the output of the `*_indices` functions
+pub fn gather(points: &[Point2], indices: &[usize]) -> Vec<Point2> {
+    indices.iter()
+        .map(|&i| points[i])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flat_roundtrip() {
+        let flat = vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0];
+        let points = from_flat(&flat);
+
+        assert_eq!(points, vec![Point2::new(0., 1.), Point2::new(2., 3.), Point2::new(4., 5.)]);
+        assert_eq!(to_flat(&points), flat);
+        assert_eq!(try_from_flat(&flat[1..]), Err(HullError::OddLength { len: 5 }));
+    }
+
+    #[test]
+    #[should_panic(expected = "andrew: odd number of coordinates (3) for 2D points")]
+    fn flat_wrapper_odd_length() {
+        crate::andrew(&[0., 0., 1.]);
+    }
+
+    #[test]
+    fn operators() {
+        let a = Point2::new(1., 2.);
+        let b = Point2::new(3., -1.);
+
+        assert_eq!(a + b, Point2::new(4., 1.));
+        assert_eq!(a - b, Point2::new(-2., 3.));
+        assert_eq!(a * 2., Point2::new(2., 4.));
+        assert_eq!(b / 2., Point2::new(1.5, -0.5));
+        assert_eq!(-a, Point2::new(-1., -2.));
+        assert_eq!(a.cross(b), -7.);
+        assert_eq!(a.dot(b), 1.);
+    }
+}
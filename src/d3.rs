@@ -1,6 +1,7 @@
-use std::ops::{Add, Sub, DivAssign, Div};
+use std::ops::{Add, Sub, Mul, AddAssign, DivAssign, Div};
 
-const EPS: f64 = 1e-4;
+use crate::predicates::orient3d;
+use crate::mesh::HullMesh3;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Point3 {
@@ -52,6 +53,14 @@ impl Sub for Point3 {
     }
 }
 
+impl Mul<f64> for Point3 {
+    type Output = Point3;
+
+    fn mul(self, factor: f64) -> Point3 {
+        Point3::new(self.x * factor, self.y * factor, self.z * factor)
+    }
+}
+
 impl Div<f64> for Point3 {
     type Output = Point3;
 
@@ -63,6 +72,14 @@ impl Div<f64> for Point3 {
     }
 }
 
+impl AddAssign for Point3 {
+    fn add_assign(&mut self, other: Point3) {
+        self.x += other.x;
+        self.y += other.y;
+        self.z += other.z;
+    }
+}
+
 impl DivAssign<f64> for Point3 {
     fn div_assign(&mut self, divisor: f64) {
         self.x /= divisor;
@@ -71,11 +88,13 @@ impl DivAssign<f64> for Point3 {
     }
 }
 
+#[cfg(feature = "visual")]
 #[derive(Debug, Clone)]
 pub struct Edge3 {
     pub vertices: [Point3; 2],
 }
 
+#[cfg(feature = "visual")]
 impl PartialEq for Edge3 {
     // edges are symmetric
     fn eq(&self, other: &Edge3) -> bool {
@@ -106,8 +125,30 @@ impl Facet3 {
         (self.vertices[0] + self.vertices[1] + self.vertices[2])/3.
     }
 
+    /// the point of the facet closest to `p`
+    pub fn closest_point(&self, p: Point3) -> Point3 {
+        let [a, b, c] = self.vertices;
+        let n = self.normal();
+        // projection onto the plane, which is the answer if it is inside of the triangle
+        let q = p - n * n.dot(p - a);
+        let inside = [(a, b), (b, c), (c, a)].iter()
+            .all(|&(u, v)| (v - u).cross(q - u).dot(n) >= 0.);
+        if inside {
+            return q
+        }
+
+        [(a, b), (b, c), (c, a)].iter()
+            .map(|&(u, v)| {
+                let uv = v - u;
+                let t = ((p - u).dot(uv) / uv.dot(uv)).clamp(0., 1.);
+                u + uv * t
+            })
+            .fold(a, |min, x| if (x - p).length() < (min - p).length() { x } else { min })
+    }
+
+    /// whether `q` lies strictly in front of the facet, evaluated exactly
     pub fn visible_from(&self, q: &Point3) -> bool {
-        (*q - self.vertices[0]).dot(self.normal()) > EPS
+        orient3d(self.vertices[0], self.vertices[1], self.vertices[2], *q) > 0.
     }
 }
 
@@ -115,6 +156,86 @@ pub fn surface(facets: &[Facet3]) -> f64 {
     facets.iter().map(|f| f.surface()).sum()
 }
 
+/// width of the hull averaged over all directions, see `HullMesh3::mean_width`
+pub fn mean_width(facets: &[Facet3]) -> f64 {
+    HullMesh3::from_facets(facets).mean_width()
+}
+
+/// volume, centroid relative to `o` and second moment `∫ r r^T dV` relative
+/// to `o` of the body enclosed by the facets
+///
+/// the body is decomposed into tetrahedra spanned by `o` and every facet,
+/// which is exact for closed surfaces oriented counter-clockwise from outside
+fn moments(facets: &[Facet3], o: Point3) -> (f64, Point3, [[f64; 3]; 3]) {
+    let mut volume = 0.;
+    let mut first = Point3::new(0., 0., 0.);
+    let mut second = [[0.; 3]; 3];
+
+    for f in facets {
+        let [a, b, c] = f.vertices.map(|v| v - o);
+        let det = a.dot(b.cross(c));
+        let sum = a + b + c;
+
+        volume += det / 6.;
+        first += sum * (det / 24.);
+        let v = [a, b, c, sum].map(|p| [p.x, p.y, p.z]);
+        for i in 0..3 {
+            for j in 0..3 {
+                let s: f64 = v.iter().map(|p| p[i] * p[j]).sum();
+                second[i][j] += s * det / 120.;
+            }
+        }
+    }
+
+    let centroid = if volume > 0. { first / volume } else { first };
+    (volume, centroid, second)
+}
+
+/// volume enclosed by the facets of a hull
+pub fn volume(facets: &[Facet3]) -> f64 {
+    match facets.first() {
+        Some(f) => moments(facets, f.vertices[0]).0,
+        None => 0.,
+    }
+}
+
+/// centroid of the volume enclosed by the facets of a hull
+pub fn centroid(facets: &[Facet3]) -> Point3 {
+    match facets.first() {
+        Some(f) => {
+            let o = f.vertices[0];
+            moments(facets, o).1 + o
+        },
+        None => Point3::new(0., 0., 0.),
+    }
+}
+
+/// inertia tensor of the hull with unit density with respect to its centroid
+pub fn inertia_tensor(facets: &[Facet3]) -> [[f64; 3]; 3] {
+    let o = match facets.first() {
+        Some(f) => f.vertices[0],
+        None => return [[0.; 3]; 3],
+    };
+    let (volume, c, mut second) = moments(facets, o);
+
+    // parallel axis theorem, to move the second moment to the centroid
+    let c = [c.x, c.y, c.z];
+    for i in 0..3 {
+        for j in 0..3 {
+            second[i][j] -= volume * c[i] * c[j];
+        }
+    }
+
+    let trace = second[0][0] + second[1][1] + second[2][2];
+    let mut inertia = [[0.; 3]; 3];
+    for i in 0..3 {
+        for j in 0..3 {
+            inertia[i][j] = if i == j { trace } else { 0. } - second[i][j];
+        }
+    }
+    inertia
+}
+
 #[cfg(feature = "visual")] use std::io;
 #[cfg(feature = "visual")] use std::io::prelude::*;
 #[cfg(feature = "visual")] use std::fs::File;
@@ -133,7 +254,7 @@ pub fn threejs(
     let path = Path::new(filename);
 
     // Open a file in write-only mode, returns `io::Result<File>`
-    let mut file = File::create(&path)?;
+    let mut file = File::create(path)?;
 
     /* Write Header */
     write!(file,
@@ -346,3 +467,46 @@ fn print_edge(edge: &Edge3) -> String {
              0.5
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+    use crate::quickhull3d;
+    use crate::util::tests::get_cube;
+
+    #[test]
+    fn cube_moments() {
+        // shifted away from the origin, which must not matter
+        let v: Vec<Point3> = get_cube().into_iter().map(|p| p + Point3::new(10., -20., 30.)).collect();
+        let hull = quickhull3d(&v);
+
+        assert_approx_eq!(volume(&hull), 1.);
+        assert_approx_eq!(mean_width(&hull), 1.5);
+        let c = centroid(&hull);
+        assert_approx_eq!(c.x, 10.5);
+        assert_approx_eq!(c.y, -19.5);
+        assert_approx_eq!(c.z, 30.5);
+
+        let inertia = inertia_tensor(&hull);
+        for (i, row) in inertia.iter().enumerate() {
+            for (j, &x) in row.iter().enumerate() {
+                assert_approx_eq!(x, if i == j { 1. / 6. } else { 0. });
+            }
+        }
+    }
+
+    #[test]
+    fn tetrahedron_moments() {
+        let p = Point3::new;
+        let v = vec![p(0., 0., 0.), p(1., 0., 0.), p(0., 1., 0.), p(0., 0., 1.)];
+        let hull = quickhull3d(&v);
+
+        assert_approx_eq!(volume(&hull), 1. / 6.);
+        assert_approx_eq!(centroid(&hull).x, 0.25);
+        // I_xx = ∫ y^2 + z^2 dV = 1/30 at the origin, shifted to the centroid
+        let inertia = inertia_tensor(&hull);
+        assert_approx_eq!(inertia[0][0], 1. / 30. - 1. / 6. * 2. / 16.);
+        assert_approx_eq!(inertia[0][1], -(1. / 120. - 1. / 6. / 16.));
+    }
+}
@@ -0,0 +1,125 @@
+use std::fmt;
+
+use crate::d2::Point2;
+use crate::d3::Point3;
+use crate::predicates::{orient2d, orient3d, collinear3d};
+
+/// reasons why no proper hull can be constructed from the input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HullError {
+    /// less distinct points than needed to span a hull in this dimension
+    TooFewPoints { found: usize, required: usize },
+    /// all points lie on a single line
+    AllCollinear,
+    /// all points lie on a single plane
+    AllCoplanar,
+    /// the point at `index` has a NaN or infinite coordinate
+    NonFinite { index: usize },
+    /// a flat coordinate array with an odd number of values
+    OddLength { len: usize },
+}
+
+impl fmt::Display for HullError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HullError::TooFewPoints { found, required } =>
+                write!(f, "found {} distinct points, but at least {} are required", found, required),
+            HullError::AllCollinear => write!(f, "all points are collinear"),
+            HullError::AllCoplanar => write!(f, "all points are coplanar"),
+            HullError::NonFinite { index } => write!(f, "point {} has a non-finite coordinate", index),
+            HullError::OddLength { len } => write!(f, "odd number of coordinates ({}) for 2D points", len),
+        }
+    }
+}
+
+impl std::error::Error for HullError {}
+
+/// checks that the points are finite and span a polygon of non-zero area
+pub fn validate_2d(pointset: &[Point2]) -> Result<(), HullError> {
+    if let Some(index) = pointset.iter().position(|p| !(p.x.is_finite() && p.y.is_finite())) {
+        return Err(HullError::NonFinite { index })
+    }
+
+    let too_few = |found| Err(HullError::TooFewPoints { found, required: 3 });
+
+    let a = match pointset.first() {
+        Some(&a) => a,
+        None => return too_few(0),
+    };
+    let b = match pointset.iter().find(|&&p| p != a) {
+        Some(&b) => b,
+        None => return too_few(1),
+    };
+    if pointset.iter().any(|&p| orient2d(a, b, p) != 0.) {
+        return Ok(())
+    }
+
+    if pointset.iter().any(|&p| p != a && p != b) {
+        Err(HullError::AllCollinear)
+    } else {
+        too_few(2)
+    }
+}
+
+/// checks that the points are finite and span a polytope of non-zero volume
+pub fn validate_3d(pointset: &[Point3]) -> Result<(), HullError> {
+    if let Some(index) = pointset.iter().position(|p| !(p.x.is_finite() && p.y.is_finite() && p.z.is_finite())) {
+        return Err(HullError::NonFinite { index })
+    }
+
+    let too_few = |found| Err(HullError::TooFewPoints { found, required: 4 });
+
+    let a = match pointset.first() {
+        Some(&a) => a,
+        None => return too_few(0),
+    };
+    let b = match pointset.iter().find(|&&p| p != a) {
+        Some(&b) => b,
+        None => return too_few(1),
+    };
+    let c = match pointset.iter().find(|&&p| !collinear3d(a, b, p)) {
+        Some(&c) => c,
+        None => {
+            return if pointset.iter().any(|&p| p != a && p != b) {
+                Err(HullError::AllCollinear)
+            } else {
+                too_few(2)
+            }
+        }
+    };
+    if pointset.iter().any(|&p| orient3d(a, b, c, p) != 0.) {
+        Ok(())
+    } else if pointset.iter().any(|&p| p != a && p != b && p != c) {
+        Err(HullError::AllCoplanar)
+    } else {
+        too_few(3)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn degenerate_2d() {
+        let p = |x, y| Point2::new(x, y);
+
+        assert_eq!(validate_2d(&[]), Err(HullError::TooFewPoints { found: 0, required: 3 }));
+        assert_eq!(validate_2d(&[p(1., 1.); 5]), Err(HullError::TooFewPoints { found: 1, required: 3 }));
+        assert_eq!(validate_2d(&[p(0., 0.), p(1., 1.), p(0., 0.)]), Err(HullError::TooFewPoints { found: 2, required: 3 }));
+        assert_eq!(validate_2d(&[p(0., 0.), p(1., 1.), p(3., 3.)]), Err(HullError::AllCollinear));
+        assert_eq!(validate_2d(&[p(0., 0.), p(1., f64::NAN), p(3., 3.)]), Err(HullError::NonFinite { index: 1 }));
+        assert_eq!(validate_2d(&[p(0., 0.), p(1., 0.), p(0., 1.)]), Ok(()));
+    }
+
+    #[test]
+    fn degenerate_3d() {
+        let p = Point3::new;
+
+        assert_eq!(validate_3d(&[p(0., 0., 0.), p(1., 1., 1.), p(2., 2., 2.)]), Err(HullError::AllCollinear));
+        assert_eq!(validate_3d(&[p(0., 0., 0.), p(1., 0., 0.), p(0., 1., 0.), p(1., 1., 0.)]), Err(HullError::AllCoplanar));
+        assert_eq!(validate_3d(&[p(0., 0., 0.), p(1., 0., 0.), p(0., 1., 0.)]), Err(HullError::TooFewPoints { found: 3, required: 4 }));
+        assert_eq!(validate_3d(&[p(0., 0., f64::INFINITY)]), Err(HullError::NonFinite { index: 0 }));
+        assert_eq!(validate_3d(&[p(0., 0., 0.), p(1., 0., 0.), p(0., 1., 0.), p(0., 0., 1.)]), Ok(()));
+    }
+}
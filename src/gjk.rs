@@ -0,0 +1,647 @@
+use std::collections::HashMap;
+use std::ops::{Add, Sub, Mul};
+
+use crate::d2::Point2;
+use crate::d3::{Point3, Facet3};
+use crate::convex::{ConvexPolygon, ConvexPolytope};
+use crate::mesh::HullMesh3;
+use crate::predicates::orient3d;
+
+// Gilbert, Johnson and Keerthi, "A fast procedure for computing the distance
+// between complex objects in three-dimensional space",
+// IEEE Journal on Robotics and Automation 4, 193 (1988).
+//
+// The distance of two convex sets is the distance of their Minkowski
+// difference A - B from the origin. GJK approaches the closest point of
+// A - B by a sequence of simplices, whose vertices are differences of
+// support points of A and B, such that neither set is needed explicitly.
+//
+// van den Bergen, "Proximity queries and penetration depth computation on
+// 3D game objects", Game Developers Conference (2001).
+//
+// If the sets intersect, GJK ends with a simplex around the origin. The
+// expanding polytope algorithm (EPA) grows it towards the boundary of
+// A - B until the facet closest to the origin is found, whose distance is
+// the penetration depth.
+
+/// maximum number of iterations, which is only reached due to rounding
+const MAX_ITERATIONS: usize = 1000;
+
+/// relative tolerance of the convergence tests
+const EPS: f64 = 1e-10;
+
+/// sets, which know their furthest point in every direction
+pub trait Support2d {
+    /// a point of the set with maximal projection on `direction`
+    fn support_point(&self, direction: Point2) -> Point2;
+}
+
+/// sets, which know their furthest point in every direction
+pub trait Support3d {
+    /// a point of the set with maximal projection on `direction`
+    fn support_point(&self, direction: Point3) -> Point3;
+}
+
+impl Support2d for ConvexPolygon {
+    fn support_point(&self, direction: Point2) -> Point2 {
+        self.vertices[self.support(direction)]
+    }
+}
+
+/// a linear scan over all vertices, `ConvexPolytope` or `HullMesh3` are
+/// faster for repeated queries
+///
+/// # Panics
+/// if there are no facets
+impl Support3d for [Facet3] {
+    fn support_point(&self, direction: Point3) -> Point3 {
+        let first = self.first().expect("the support of an empty set of facets is undefined");
+        self.iter()
+            .flat_map(|f| f.vertices)
+            .fold(first.vertices[0], |max, p| if p.dot(direction) > max.dot(direction) { p } else { max })
+    }
+}
+
+impl Support3d for ConvexPolytope {
+    fn support_point(&self, direction: Point3) -> Point3 {
+        self.support(direction)
+    }
+}
+
+impl Support3d for HullMesh3 {
+    fn support_point(&self, direction: Point3) -> Point3 {
+        self.vertices[self.support(direction)]
+    }
+}
+
+/// distance of two convex sets
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Distance<P> {
+    /// zero if the sets intersect
+    pub distance: f64,
+    /// the closest points of the first and the second set
+    pub closest: (P, P),
+}
+
+/// overlap of two intersecting convex sets
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Penetration<P> {
+    /// the shortest translation, which separates the sets
+    pub depth: f64,
+    /// unit vector pointing from the first towards the second set, i.e.,
+    /// translating the second set by `depth * normal` separates them
+    pub normal: P,
+}
+
+/// the operations of GJK, which are the same in 2D and 3D
+trait Vector: Copy + Add<Output = Self> + Sub<Output = Self> + Mul<f64, Output = Self> {
+    const DIM: usize;
+
+    fn dot(self, other: Self) -> f64;
+
+    /// directions orthogonal to the affine hull spanned by `edges`
+    fn normals(edges: &[Self]) -> Vec<Self>;
+}
+
+impl Vector for Point2 {
+    const DIM: usize = 2;
+
+    fn dot(self, other: Self) -> f64 {
+        Point2::dot(self, other)
+    }
+
+    fn normals(edges: &[Self]) -> Vec<Self> {
+        match edges {
+            [] => vec![Point2::new(1., 0.), Point2::new(0., 1.)],
+            [e] => vec![Point2::new(-e.y, e.x)],
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl Vector for Point3 {
+    const DIM: usize = 3;
+
+    fn dot(self, other: Self) -> f64 {
+        Point3::dot(self, other)
+    }
+
+    fn normals(edges: &[Self]) -> Vec<Self> {
+        match edges {
+            [] => vec![Point3::new(1., 0., 0.), Point3::new(0., 1., 0.), Point3::new(0., 0., 1.)],
+            [e] => {
+                // the axis least aligned with `e`
+                let axis = if e.x.abs() <= e.y.abs() && e.x.abs() <= e.z.abs() {
+                    Point3::new(1., 0., 0.)
+                } else if e.y.abs() <= e.z.abs() {
+                    Point3::new(0., 1., 0.)
+                } else {
+                    Point3::new(0., 0., 1.)
+                };
+                let n = e.cross(axis);
+                vec![n, e.cross(n)]
+            },
+            [e, f] => vec![e.cross(*f)],
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// a point of the Minkowski difference `w = a - b` with its origins
+#[derive(Debug, Clone, Copy)]
+struct Vertex<P> {
+    w: P,
+    a: P,
+    b: P,
+}
+
+/// barycentric coordinates of the point of the affine hull of `points`
+/// closest to the origin, `None` if the points are affinely dependent
+fn barycentric<P: Vector>(points: &[P]) -> Option<Vec<f64>> {
+    let m = points.len() - 1;
+    let edges: Vec<P> = points[1..].iter().map(|&p| p - points[0]).collect();
+
+    // normal equations of the edge coefficients, solved by gaussian elimination
+    let mut gram: Vec<Vec<f64>> = (0..m)
+        .map(|i| {
+            let mut row: Vec<f64> = (0..m).map(|j| edges[i].dot(edges[j])).collect();
+            row.push(-edges[i].dot(points[0]));
+            row
+        })
+        .collect();
+    let scale = (0..m).map(|i| gram[i][i]).fold(0., f64::max);
+
+    for col in 0..m {
+        let pivot = (col..m).max_by(|&i, &j| gram[i][col].abs().total_cmp(&gram[j][col].abs()))?;
+        if gram[pivot][col].abs() <= 1e-12 * scale {
+            return None
+        }
+        gram.swap(col, pivot);
+        let pivot_row = gram[col].clone();
+        for row in gram[col+1..].iter_mut() {
+            let factor = row[col] / pivot_row[col];
+            for (x, p) in row[col..].iter_mut().zip(&pivot_row[col..]) {
+                *x -= factor * p;
+            }
+        }
+    }
+
+    let mut mu = vec![0.; m];
+    for row in (0..m).rev() {
+        let rest: f64 = (row+1..m).map(|k| gram[row][k] * mu[k]).sum();
+        mu[row] = (gram[row][m] - rest) / gram[row][row];
+    }
+
+    let mut lambda = vec![1. - mu.iter().sum::<f64>()];
+    lambda.extend(mu);
+    Some(lambda)
+}
+
+/// the point of the simplex closest to the origin, together with the
+/// smallest face containing it and its barycentric coordinates there
+///
+/// the simplices have at most four vertices, such that all faces are tried
+fn closest<P: Vector>(simplex: &[Vertex<P>]) -> (P, Vec<Vertex<P>>, Vec<f64>) {
+    let k = simplex.len();
+    let face = |mask: usize| -> Vec<Vertex<P>> {
+        (0..k).filter(|i| mask >> i & 1 == 1).map(|i| simplex[i]).collect()
+    };
+    let mut best: Option<(f64, P, usize, Vec<f64>)> = None;
+
+    for mask in 1..(1usize << k) {
+        let points: Vec<P> = face(mask).iter().map(|v| v.w).collect();
+        let Some(lambda) = barycentric(&points) else { continue };
+        // only points inside of the face are points of the simplex
+        if lambda.iter().any(|&l| l <= 0.) {
+            continue
+        }
+
+        let x = (1..points.len()).fold(points[0] * lambda[0], |x, i| x + points[i] * lambda[i]);
+        let d = x.dot(x);
+        if best.as_ref().is_none_or(|b| d < b.0) {
+            best = Some((d, x, mask, lambda));
+        }
+    }
+
+    // the vertices are always valid faces
+    let (_, x, mask, lambda) = best.unwrap();
+    (x, face(mask), lambda)
+}
+
+/// result of GJK
+struct Gjk<P> {
+    intersecting: bool,
+    simplex: Vec<Vertex<P>>,
+    lambda: Vec<f64>,
+    /// largest squared length of all support points, for relative tolerances
+    scale: f64,
+}
+
+/// GJK on the Minkowski difference of two sets given by support functions
+///
+/// if `separate` is set, it stops as soon as a separating plane is found,
+/// which suffices to decide whether the sets intersect
+fn gjk<P: Vector>(sa: impl Fn(P) -> P, sb: impl Fn(P) -> P, separate: bool) -> Gjk<P> {
+    let support = |d: P| {
+        let (a, b) = (sa(d), sb(d * -1.));
+        Vertex { w: a - b, a, b }
+    };
+
+    let first = support(P::normals(&[])[0]);
+    let mut v = first.w;
+    let mut simplex = vec![first];
+    let mut lambda = vec![1.];
+    let mut scale = v.dot(v);
+
+    for _ in 0..MAX_ITERATIONS {
+        let vv = v.dot(v);
+        if vv <= EPS * EPS * scale {
+            return Gjk { intersecting: true, simplex, lambda, scale }
+        }
+
+        let next = support(v * -1.);
+        scale = scale.max(next.w.dot(next.w));
+        let vw = v.dot(next.w);
+        if separate && vw > 0. {
+            break
+        }
+        // no support point is closer to the origin than the current one
+        if vv - vw <= EPS * vv {
+            break
+        }
+
+        let mut candidate = simplex.clone();
+        candidate.push(next);
+        let (x, face, l) = closest(&candidate);
+        // rounding prevents any progress
+        if x.dot(x) >= vv {
+            break
+        }
+        v = x;
+        simplex = face;
+        lambda = l;
+
+        // the origin is inside of a full dimensional simplex
+        if simplex.len() == P::DIM + 1 {
+            return Gjk { intersecting: true, simplex, lambda, scale }
+        }
+    }
+
+    Gjk { intersecting: false, simplex, lambda, scale }
+}
+
+fn distance<P: Vector>(sa: impl Fn(P) -> P, sb: impl Fn(P) -> P) -> Distance<P> {
+    let result = gjk(sa, sb, false);
+    let combine = |f: fn(&Vertex<P>) -> P| {
+        (1..result.simplex.len())
+            .fold(f(&result.simplex[0]) * result.lambda[0], |x, i| x + f(&result.simplex[i]) * result.lambda[i])
+    };
+    let (a, b) = (combine(|v| v.a), combine(|v| v.b));
+    let d = a - b;
+
+    Distance {
+        distance: if result.intersecting { 0. } else { d.dot(d).sqrt() },
+        closest: (a, b),
+    }
+}
+
+/// extends the simplex of an intersection found by GJK to full dimension,
+/// `None` if the Minkowski difference is flat
+fn full_simplex<P: Vector>(sa: impl Fn(P) -> P, sb: impl Fn(P) -> P, result: &Gjk<P>) -> Option<Vec<P>> {
+    let mut simplex: Vec<P> = result.simplex.iter().map(|v| v.w).collect();
+    let tolerance = EPS * result.scale.sqrt();
+
+    while simplex.len() <= P::DIM {
+        let edges: Vec<P> = simplex[1..].iter().map(|&p| p - simplex[0]).collect();
+        let next = P::normals(&edges).into_iter()
+            .flat_map(|n| [n, n * -1.])
+            .map(|n| (n, sa(n) - sb(n * -1.)))
+            .find(|&(n, w)| n.dot(w - simplex[0]) > tolerance * n.dot(n).sqrt())?;
+        simplex.push(next.1);
+    }
+
+    Some(simplex)
+}
+
+fn epa_2d(sa: impl Fn(Point2) -> Point2, sb: impl Fn(Point2) -> Point2) -> Option<Penetration<Point2>> {
+    let result = gjk(&sa, &sb, false);
+    if !result.intersecting {
+        return None
+    }
+    let tolerance = EPS * result.scale.sqrt();
+    let Some(mut polygon) = full_simplex(&sa, &sb, &result) else {
+        return Some(Penetration { depth: 0., normal: Point2::new(1., 0.) })
+    };
+    if (polygon[1] - polygon[0]).cross(polygon[2] - polygon[0]) < 0. {
+        polygon.swap(1, 2);
+    }
+
+    let mut best = None;
+    for _ in 0..MAX_ITERATIONS {
+        // the edge closest to the origin with its outward normal
+        let (i, depth, normal) = (0..polygon.len())
+            .map(|i| {
+                let e = polygon[(i + 1) % polygon.len()] - polygon[i];
+                let n = Point2::new(e.y, -e.x) / e.length();
+                (i, n.dot(polygon[i]), n)
+            })
+            .fold((0, f64::INFINITY, Point2::new(1., 0.)), |min, c| if c.1 < min.1 { c } else { min });
+        best = Some(Penetration { depth: depth.max(0.), normal });
+
+        let w = sa(normal) - sb(-normal);
+        if normal.dot(w) - depth <= tolerance {
+            break
+        }
+        polygon.insert(i + 1, w);
+    }
+
+    best
+}
+
+/// a facet of the expanding polytope, counter-clockwise seen from outside
+struct Face {
+    vertices: [usize; 3],
+    normal: Point3,
+    distance: f64,
+}
+
+impl Face {
+    fn new(points: &[Point3], vertices: [usize; 3]) -> Face {
+        let [a, b, c] = vertices.map(|i| points[i]);
+        let n = (b - a).cross(c - a);
+        let normal = n / n.length();
+        Face { vertices, normal, distance: normal.dot(a) }
+    }
+}
+
+fn epa_3d(sa: impl Fn(Point3) -> Point3, sb: impl Fn(Point3) -> Point3) -> Option<Penetration<Point3>> {
+    let result = gjk(&sa, &sb, false);
+    if !result.intersecting {
+        return None
+    }
+    let tolerance = EPS * result.scale.sqrt();
+    let Some(mut points) = full_simplex(&sa, &sb, &result) else {
+        return Some(Penetration { depth: 0., normal: Point3::new(1., 0., 0.) })
+    };
+
+    // orient the tetrahedron, such that its facets face outward
+    let [a, b, c, d] = [points[0], points[1], points[2], points[3]];
+    if (b - a).cross(c - a).dot(d - a) > 0. {
+        points.swap(1, 2);
+    }
+    let mut faces: Vec<Face> = [[0, 1, 2], [0, 3, 1], [0, 2, 3], [1, 3, 2]].into_iter()
+        .map(|f| Face::new(&points, f))
+        .collect();
+
+    let mut best = None;
+    for _ in 0..MAX_ITERATIONS {
+        let closest = (0..faces.len())
+            .min_by(|&f, &g| faces[f].distance.total_cmp(&faces[g].distance))?;
+        let (depth, normal) = (faces[closest].distance, faces[closest].normal);
+        best = Some(Penetration { depth: depth.max(0.), normal });
+
+        let w = sa(normal) - sb(normal * -1.);
+        let visible_from_w = |f: &Face| {
+            let [a, b, c] = f.vertices.map(|i| points[i]);
+            orient3d(a, b, c, w) > 0.
+        };
+        // the visibility is evaluated exactly, since a face, which is kept
+        // although `w` is slightly in front of it, makes the polytope concave
+        if normal.dot(w) - depth <= tolerance || !visible_from_w(&faces[closest]) {
+            break
+        }
+
+        // the faces visible from `w` are a connected patch around the closest
+        // face, which is replaced by a cone from its horizon to `w`
+        let twins: HashMap<(usize, usize), usize> = faces.iter().enumerate()
+            .flat_map(|(f, face)| {
+                let [a, b, c] = face.vertices;
+                [((a, b), f), ((b, c), f), ((c, a), f)]
+            })
+            .collect();
+        let mut visible = vec![false; faces.len()];
+        visible[closest] = true;
+        let mut stack = vec![closest];
+        let mut horizon = Vec::new();
+        while let Some(f) = stack.pop() {
+            let [a, b, c] = faces[f].vertices;
+            for (u, v) in [(a, b), (b, c), (c, a)] {
+                let g = twins[&(v, u)];
+                if visible[g] {
+                    continue
+                }
+                if visible_from_w(&faces[g]) {
+                    visible[g] = true;
+                    stack.push(g);
+                } else {
+                    horizon.push((u, v));
+                }
+            }
+        }
+
+        points.push(w);
+        let apex = points.len() - 1;
+        faces = faces.into_iter()
+            .zip(visible)
+            .filter_map(|(face, visible)| (!visible).then_some(face))
+            .collect();
+        for (a, b) in horizon {
+            faces.push(Face::new(&points, [a, b, apex]));
+        }
+    }
+
+    best
+}
+
+/// whether two convex polygons intersect or touch
+pub fn gjk_intersects<A: Support2d + ?Sized, B: Support2d + ?Sized>(a: &A, b: &B) -> bool {
+    gjk(|d| a.support_point(d), |d| b.support_point(d), true).intersecting
+}
+
+/// whether two 3D hulls intersect or touch
+pub fn gjk_intersects_3d<A: Support3d + ?Sized, B: Support3d + ?Sized>(a: &A, b: &B) -> bool {
+    gjk(|d| a.support_point(d), |d| b.support_point(d), true).intersecting
+}
+
+/// distance of two convex polygons and their closest points
+pub fn gjk_distance<A: Support2d + ?Sized, B: Support2d + ?Sized>(a: &A, b: &B) -> Distance<Point2> {
+    distance(|d| a.support_point(d), |d| b.support_point(d))
+}
+
+/// distance of two 3D hulls and their closest points
+pub fn gjk_distance_3d<A: Support3d + ?Sized, B: Support3d + ?Sized>(a: &A, b: &B) -> Distance<Point3> {
+    distance(|d| a.support_point(d), |d| b.support_point(d))
+}
+
+/// penetration depth and contact normal of two convex polygons, `None` if
+/// they do not intersect
+pub fn epa_penetration<A: Support2d + ?Sized, B: Support2d + ?Sized>(a: &A, b: &B) -> Option<Penetration<Point2>> {
+    epa_2d(|d| a.support_point(d), |d| b.support_point(d))
+}
+
+/// penetration depth and contact normal of two 3D hulls, `None` if they do
+/// not intersect
+pub fn epa_penetration_3d<A: Support3d + ?Sized, B: Support3d + ?Sized>(a: &A, b: &B) -> Option<Penetration<Point3>> {
+    epa_3d(|d| a.support_point(d), |d| b.support_point(d))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+    use crate::d2::from_flat;
+    use crate::util::{get_test_vector_gaussian, get_test_vector_gaussian_3d};
+    use rand::{Rng, SeedableRng};
+    use rand_distr::StandardNormal;
+    use rand_pcg::Pcg64;
+    use crate::util::tests::{get_simple_cube, get_random_lattice_3d};
+    use crate::{minkowski_difference, minkowski_difference_3d, quickhull3d, try_quickhull3d};
+
+    fn square(x: f64, y: f64) -> ConvexPolygon {
+        let p = |a: f64, b: f64| Point2::new(a + x, b + y);
+        ConvexPolygon::new(vec![p(0., 0.), p(1., 0.), p(1., 1.), p(0., 1.)])
+    }
+
+    fn cube(x: f64, y: f64, z: f64) -> Vec<Facet3> {
+        let v: Vec<Point3> = get_simple_cube().into_iter().map(|p| p + Point3::new(x, y, z)).collect();
+        quickhull3d(&v)
+    }
+
+    #[test]
+    fn gjk_squares() {
+        let a = square(0., 0.);
+
+        assert!(gjk_intersects(&a, &square(0.5, 0.5)));
+        assert!(gjk_intersects(&a, &square(1., 0.)));
+        assert!(!gjk_intersects(&a, &square(2., 0.5)));
+
+        let d = gjk_distance(&a, &square(3., 0.5));
+        assert_approx_eq!(d.distance, 2.);
+        assert_approx_eq!(d.closest.0.x, 1.);
+        assert_approx_eq!(d.closest.1.x, 3.);
+        assert_approx_eq!(gjk_distance(&a, &square(4., 5.)).distance, 5.);
+        assert_eq!(gjk_distance(&a, &square(0.5, 0.5)).distance, 0.);
+
+        let p = epa_penetration(&a, &square(0.8, 0.5)).unwrap();
+        assert_approx_eq!(p.depth, 0.2);
+        assert_approx_eq!(p.normal.x, 1.);
+        assert_approx_eq!(p.normal.y, 0.);
+        assert!(epa_penetration(&a, &square(2., 0.)).is_none());
+        assert_approx_eq!(epa_penetration(&a, &square(1., 0.)).unwrap().depth, 0.);
+    }
+
+    #[test]
+    fn gjk_cubes() {
+        let a = cube(0., 0., 0.);
+
+        assert!(gjk_intersects_3d(&a[..], &cube(0.5, 0.5, 0.5)[..]));
+        assert!(!gjk_intersects_3d(&a[..], &cube(0.5, 2., 0.5)[..]));
+
+        let d = gjk_distance_3d(&a[..], &cube(2., 3., 1.)[..]);
+        assert_approx_eq!(d.distance, 5f64.sqrt());
+
+        let p = epa_penetration_3d(&a[..], &cube(0.1, 0.7, 0.2)[..]).unwrap();
+        assert_approx_eq!(p.depth, 0.3);
+        assert_approx_eq!(p.normal.y, 1.);
+        assert!(epa_penetration_3d(&a[..], &cube(0.5, 2., 0.5)[..]).is_none());
+
+        let mesh = HullMesh3::from_facets(&a);
+        let polytope = ConvexPolytope::new(cube(0.5, 0., 1.5));
+        assert_approx_eq!(gjk_distance_3d(&mesh, &polytope).distance, 0.5);
+    }
+
+    #[test]
+    fn gjk_random_2d() {
+        let a = ConvexPolygon::from_points(&from_flat(&get_test_vector_gaussian(200)));
+        let b = ConvexPolygon::from_points(&from_flat(&get_test_vector_gaussian(400))[200..]);
+
+        for (x, y) in [(0., 0.), (1., 2.), (-3., 0.5), (6., -4.), (0.2, 5.), (-2., -3.)] {
+            let shifted = ConvexPolygon::new(b.vertices.iter().map(|&p| p + Point2::new(x, y)).collect());
+
+            // the distance of the Minkowski difference from the origin
+            let cso = ConvexPolygon::new(minkowski_difference(&a.vertices, &shifted.vertices));
+            let (expected, _) = cso.signed_distance(Point2::new(0., 0.));
+
+            assert_eq!(gjk_intersects(&a, &shifted), expected < 0.);
+            let d = gjk_distance(&a, &shifted);
+            assert_approx_eq!(d.distance, expected.max(0.));
+            assert_approx_eq!((d.closest.0 - d.closest.1).length(), d.distance);
+            match epa_penetration(&a, &shifted) {
+                Some(p) => assert_approx_eq!(p.depth, -expected),
+                None => assert!(expected > 0.),
+            }
+        }
+    }
+
+    #[test]
+    fn epa_brute_force_3d() {
+        let mut rng = Pcg64::seed_from_u64(42);
+        let mut intersecting = 0;
+        for seed in 0..400 {
+            // lattice hulls with many coplanar facets and gaussian clouds
+            let (a, b) = if seed % 2 == 0 {
+                (get_random_lattice_3d(seed, 8 + seed as usize % 20, 2), get_random_lattice_3d(seed + 1000, 8 + seed as usize % 13, 2))
+            } else {
+                let mut cloud = |n: usize, s: f64| -> Vec<Point3> {
+                    let mut c = || rng.sample::<f64, _>(StandardNormal) * s;
+                    (0..n).map(|_| Point3::new(c(), c(), c())).collect()
+                };
+                (cloud(10 + seed as usize % 30, 1.), cloud(10 + seed as usize % 17, 0.5))
+            };
+            let shift = Point3::new(rng.gen_range(-2..=2) as f64, rng.gen_range(-2..=2) as f64, rng.gen_range(-2..=2) as f64);
+            let b: Vec<Point3> = b.into_iter().map(|p| p + shift).collect();
+            let (Ok(a), Ok(b)) = (try_quickhull3d(&a), try_quickhull3d(&b)) else { continue };
+
+            // the distance of the origin to the closest facet of A - B
+            let cso = minkowski_difference_3d(&a, &b);
+            let o = Point3::new(0., 0., 0.);
+            if cso.iter().any(|f| f.visible_from(&o)) {
+                assert!(epa_penetration_3d(&a[..], &b[..]).is_none(), "seed {}", seed);
+                continue
+            }
+            let expected = cso.iter()
+                .map(|f| f.normal().dot(f.vertices[0]))
+                .fold(f64::INFINITY, f64::min);
+            let depth = epa_penetration_3d(&a[..], &b[..]).expect("intersecting").depth;
+            assert!((depth - expected).abs() < 1e-6, "seed {}: {} instead of {}", seed, depth, expected);
+            intersecting += 1;
+        }
+        assert!(intersecting > 200);
+    }
+
+    #[test]
+    fn gjk_random_3d() {
+        let a = quickhull3d(&get_test_vector_gaussian_3d(200));
+        let points = get_test_vector_gaussian_3d(400);
+
+        let (mut intersecting, mut disjoint) = (0, 0);
+        // overlapping clouds and clouds far enough apart to be disjoint
+        let shifts = [
+            (0., 0., 0.), (1., 2., 0.), (-3., 0.5, 1.), (6., -4., 2.), (0.2, 1., -0.5),
+            (200., 0., 0.), (-90., 150., 40.), (0., -30., -250.), (120., 120., 120.),
+        ];
+        for (x, y, z) in shifts {
+            let shifted: Vec<Point3> = points[200..].iter().map(|&p| p + Point3::new(x, y, z)).collect();
+            let b = quickhull3d(&shifted);
+
+            let cso = ConvexPolytope::new(minkowski_difference_3d(&a, &b));
+            let (expected, _) = cso.signed_distance(Point3::new(0., 0., 0.));
+
+            assert_eq!(gjk_intersects_3d(&a[..], &b[..]), expected < 0.);
+            assert_approx_eq!(gjk_distance_3d(&a[..], &b[..]).distance, expected.max(0.));
+            let (pa, pb) = (ConvexPolytope::new(a.clone()), ConvexPolytope::new(b.clone()));
+            assert_approx_eq!(gjk_distance_3d(&pa, &pb).distance, expected.max(0.));
+            match epa_penetration_3d(&a[..], &b[..]) {
+                Some(p) => {
+                    assert_approx_eq!(p.depth, -expected);
+                    intersecting += 1;
+                }
+                None => {
+                    assert!(expected > 0.);
+                    disjoint += 1;
+                }
+            }
+        }
+        assert_eq!((intersecting, disjoint), (5, 4));
+    }
+}
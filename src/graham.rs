@@ -0,0 +1,189 @@
+use std::cmp::Ordering::{Less, Greater};
+
+use crate::d2::{Point2, expect_flat, try_from_flat, to_flat, gather};
+use crate::error::{HullError, validate_2d};
+use crate::predicates::{orient2d, cmp_dist2};
+use crate::boundary::Boundary;
+use crate::primitives::all_collinear;
+
+// points stores a contiguous array of 2N floats in the format x1, y1, x2, y2, ...
+// an odd number of values panics
+pub fn graham(pointset: &[f64]) -> Vec<f64> {
+    to_flat(&graham_points(expect_flat(pointset, "graham")))
+}
+
+// points stores a contiguous array of 2N floats in the format x1, y1, x2, y2, ...
+pub fn try_graham(pointset: &[f64]) -> Result<Vec<f64>, HullError> {
+    let pointset = try_from_flat(pointset)?;
+    validate_2d(&pointset)?;
+    Ok(to_flat(&gather(&pointset, &hull(&pointset, Boundary::Extreme))))
+}
+
+pub fn graham_points<P: Into<Point2>>(pointset: impl IntoIterator<Item = P>) -> Vec<Point2> {
+    let pointset: Vec<Point2> = pointset.into_iter().map(Into::into).collect();
+    gather(&pointset, &hull(&pointset, Boundary::Extreme))
+}
+
+/// indices of the hull vertices in counter-clockwise order
+pub fn graham_indices(pointset: &[Point2]) -> Vec<usize> {
+    hull(pointset, Boundary::Extreme)
+}
+
+/// like `graham_indices`, but with `Boundary::All` the points on the edges are kept
+pub fn graham_indices_with(pointset: &[Point2], boundary: Boundary) -> Vec<usize> {
+    hull(pointset, boundary)
+}
+
+/// the lowest point (leftmost on ties) first, followed by all other points
+/// sorted by their polar angle around it, collinear ones by distance
+fn sort_polar(pointset: &[Point2]) -> Vec<usize> {
+    let pivot = (0..pointset.len())
+        .fold(0, |min, i| {
+            if (pointset[i].y, pointset[i].x) < (pointset[min].y, pointset[min].x) { i } else { min }
+        });
+    let o = pointset[pivot];
+
+    let mut sorted: Vec<usize> = (0..pointset.len())
+        .filter(|&i| pointset[i] != o)
+        .collect();
+
+    // all points are above the pivot, so the orientation is a proper order of the angles
+    sorted.sort_unstable_by(|&a, &b| {
+        let orientation = orient2d(o, pointset[a], pointset[b]);
+        if orientation > 0f64 {
+            Less
+        } else if orientation < 0f64 {
+            Greater
+        } else {
+            cmp_dist2(o, pointset[a], pointset[b])
+        }
+    });
+
+    // duplicates would be kept as collinear points
+    sorted.dedup_by(|a, b| pointset[*a] == pointset[*b]);
+    sorted.insert(0, pivot);
+
+    sorted
+}
+
+/// the points on the line back to the pivot have to be visited from the far end
+fn reverse_last_ray(pointset: &[Point2], sorted: &mut [usize]) {
+    let o = pointset[sorted[0]];
+    let last = pointset[sorted[sorted.len()-1]];
+    if let Some(k) = sorted.iter().rposition(|&i| orient2d(o, last, pointset[i]) != 0f64) {
+        sorted[k+1..].reverse();
+    }
+}
+
+#[cfg(not(feature = "visual"))]
+fn hull(pointset: &[Point2], boundary: Boundary) -> Vec<usize> {
+    if pointset.len() < 3 {
+        return (0..pointset.len()).collect()
+    }
+
+    let mut sorted = sort_polar(pointset);
+    // all points on a single ray are already in order
+    if sorted.len() < 3 || boundary == Boundary::All && all_collinear(pointset, &sorted) {
+        return sorted
+    }
+    if boundary == Boundary::All {
+        reverse_last_ray(pointset, &mut sorted);
+    }
+
+    let mut hull: Vec<usize> = Vec::new();
+    for &i in sorted.iter() {
+        while hull.len() >= 2 && boundary.drops(orient2d(pointset[hull[hull.len()-2]], pointset[hull[hull.len()-1]], pointset[i])) {
+            hull.pop();
+        }
+        hull.push(i);
+    }
+
+    hull
+}
+
+#[cfg(feature = "visual")]
+use crate::visualization::SVG;
+
+#[cfg(feature = "visual")]
+fn hull(pointset: &[Point2], boundary: Boundary) -> Vec<usize> {
+    if pointset.len() < 3 {
+        return (0..pointset.len()).collect()
+    }
+
+    let mut sorted = sort_polar(pointset);
+    // all points on a single ray are already in order
+    if sorted.len() < 3 || boundary == Boundary::All && all_collinear(pointset, &sorted) {
+        return sorted
+    }
+    if boundary == Boundary::All {
+        reverse_last_ray(pointset, &mut sorted);
+    }
+
+    let mut g = 0;
+
+    let mut hull: Vec<usize> = Vec::new();
+    for &i in sorted.iter() {
+        while hull.len() >= 2 && boundary.drops(orient2d(pointset[hull[hull.len()-2]], pointset[hull[hull.len()-1]], pointset[i])) {
+            hull.pop();
+        }
+        hull.push(i);
+
+        g += 1;
+        let filename = format!("img/graham_{:04}.svg", g);
+        let mut s = SVG::new();
+        s.points(pointset, "grey");
+        s.dashed_lines(&[pointset[sorted[0]], pointset[i]], "green");
+        s.points(&gather(pointset, &hull), "black");
+        s.lines(&gather(pointset, &hull), "black");
+        s.points(&[pointset[i]], "red");
+        s.save(&filename).expect("io error");
+    }
+
+    g += 1;
+    let filename = format!("img/graham_{:04}.svg", g);
+    let mut s = SVG::new();
+    s.points(pointset, "grey");
+    s.points(&gather(pointset, &hull), "black");
+    s.polygon(&gather(pointset, &hull), "black");
+    s.save(&filename).expect("io error");
+
+    hull
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{util::tests::{check_2048, check_square, check_square_indices, check_square_boundary}};
+    use crate::{Graham, Akl};
+
+    #[test]
+    fn graham_square() {
+        check_square(&Graham);
+    }
+
+    #[test]
+    fn graham_2048() {
+        check_2048(&Graham);
+    }
+
+    #[test]
+    fn graham_akl_square() {
+        check_square(&Akl(Graham));
+    }
+
+    #[test]
+    fn graham_akl_2048() {
+        check_2048(&Akl(Graham));
+    }
+
+    #[test]
+    fn graham_square_boundary() {
+        check_square_boundary(&Graham);
+        check_square_boundary(&Akl(Graham));
+    }
+
+    #[test]
+    fn graham_indices_square() {
+        check_square_indices(graham_indices);
+    }
+}
@@ -0,0 +1,229 @@
+use crate::d2::Point2;
+use crate::predicates::orient2d;
+
+// The hull is kept as the two monotone chains of Andrew's algorithm. A new
+// point is located in each chain by binary search. If it is outside, it is
+// inserted and its neighbours are removed as long as they do not make a left
+// turn, which happens at most once per point, such that the amortized cost
+// of an insertion is O(log n) besides the shifting of the vectors.
+//
+// The upper chain is the lower chain of the point reflected pointset, which
+// is traversed in the same direction as the upper chain in the
+// counter-clockwise hull. Both chains keep their share of the shoelace
+// formula and of the perimeter, which are updated with the edges that are
+// removed or added.
+//
+// Inserting into the middle of a chain shifts all points behind it, such
+// that an insertion is O(h) in the worst case. This is a simple memory move,
+// which is negligible for the hulls of most pointsets with h << n.
+
+/// the lower hull of the inserted points from the lexicographically first to
+/// the last one, only the extreme points
+#[derive(Debug, Clone)]
+struct Chain {
+    points: Vec<Point2>,
+    /// reference point of the shoelace sum, which avoids cancellation for
+    /// points far from the origin
+    origin: Point2,
+    /// sums of `cross` and lengths of all edges
+    cross: f64,
+    length: f64,
+}
+
+impl Chain {
+    fn new(origin: Point2) -> Chain {
+        Chain {
+            points: Vec::new(),
+            origin,
+            cross: 0.,
+            length: 0.,
+        }
+    }
+
+    /// sums of `cross` and lengths of the edges of a polyline
+    fn edges(&self, polyline: &[Point2]) -> (f64, f64) {
+        polyline.windows(2)
+            .map(|e| ((e[0] - self.origin).cross(e[1] - self.origin), (e[1] - e[0]).length()))
+            .fold((0., 0.), |(c, l), (a, b)| (c + a, l + b))
+    }
+
+    /// whether `p` changed the chain
+    fn insert(&mut self, p: Point2) -> bool {
+        let n = self.points.len();
+        let i = self.points.partition_point(|q| (q.x, q.y) < (p.x, p.y));
+
+        if i < n && self.points[i] == p {
+            return false
+        }
+        // above or on the edge, which spans `p`
+        if 0 < i && i < n && orient2d(self.points[i-1], self.points[i], p) >= 0. {
+            return false
+        }
+
+        // the neighbours, which do not make a left turn anymore
+        let (mut lo, mut hi) = (i, i);
+        while lo >= 2 && orient2d(self.points[lo-2], self.points[lo-1], p) <= 0. {
+            lo -= 1;
+        }
+        while hi + 1 < n && orient2d(p, self.points[hi], self.points[hi+1]) <= 0. {
+            hi += 1;
+        }
+
+        let (old_cross, old_length) = self.edges(&self.points[lo.saturating_sub(1)..(hi + 1).min(n)]);
+        self.points.splice(lo..hi, [p]);
+        let (new_cross, new_length) = self.edges(&self.points[lo.saturating_sub(1)..(lo + 2).min(self.points.len())]);
+
+        self.cross += new_cross - old_cross;
+        self.length += new_length - old_length;
+        true
+    }
+}
+
+/// a 2D hull, which grows by inserting points one at a time
+///
+/// area and perimeter are maintained with every insertion and available in
+/// O(1), which suits growing pointsets like the trajectory of a random walk
+#[derive(Debug, Clone)]
+pub struct IncrementalHull2d {
+    lower: Chain,
+    /// the lower chain of the negated points
+    upper: Chain,
+}
+
+impl IncrementalHull2d {
+    pub fn new() -> IncrementalHull2d {
+        let origin = Point2::new(0., 0.);
+        IncrementalHull2d {
+            lower: Chain::new(origin),
+            upper: Chain::new(origin),
+        }
+    }
+
+    /// adds `p`, which has to be finite, and returns whether the hull changed
+    ///
+    /// O(log h) amortized to locate `p` and remove the covered points, but
+    /// O(h) in the worst case to shift the points behind the inserted one
+    pub fn insert(&mut self, p: Point2) -> bool {
+        if self.is_empty() {
+            self.lower = Chain::new(p);
+            self.upper = Chain::new(-p);
+        }
+
+        let lower = self.lower.insert(p);
+        let upper = self.upper.insert(-p);
+        lower || upper
+    }
+
+    /// area of the hull
+    pub fn area(&self) -> f64 {
+        (self.lower.cross + self.upper.cross) / 2.
+    }
+
+    /// perimeter of the hull, which counts a segment twice, like `perimeter_points`
+    pub fn perimeter(&self) -> f64 {
+        self.lower.length + self.upper.length
+    }
+
+    /// number of extreme points
+    pub fn len(&self) -> usize {
+        match self.lower.points.len() {
+            0 | 1 => self.lower.points.len(),
+            n => n + self.upper.points.len() - 2,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lower.points.is_empty()
+    }
+
+    /// the extreme points in counter-clockwise order, starting at the
+    /// lexicographically smallest one, in O(h)
+    pub fn vertices(&self) -> Vec<Point2> {
+        let mut vertices = self.lower.points.clone();
+        let upper = &self.upper.points;
+        if upper.len() > 2 {
+            vertices.extend(upper[1..upper.len()-1].iter().map(|&q| -q));
+        }
+        vertices
+    }
+}
+
+impl Default for IncrementalHull2d {
+    fn default() -> IncrementalHull2d {
+        IncrementalHull2d::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+    use rand::SeedableRng;
+    use rand_distr::{Distribution, StandardNormal};
+    use rand_pcg::Pcg64;
+    use crate::andrew_points;
+    use crate::convex::ConvexPolygon;
+    use crate::primitives::{area_points, perimeter_points};
+
+    #[test]
+    fn incremental_square() {
+        let p = Point2::new;
+        let mut hull = IncrementalHull2d::new();
+        assert!(hull.is_empty());
+
+        assert!(hull.insert(p(0., 0.)));
+        assert!(!hull.insert(p(0., 0.)));
+        assert_eq!(hull.vertices(), vec![p(0., 0.)]);
+        assert_eq!(hull.len(), 1);
+
+        assert!(hull.insert(p(2., 0.)));
+        // collinear inside and outside of the segment
+        assert!(!hull.insert(p(1., 0.)));
+        assert!(hull.insert(p(4., 0.)));
+        assert_eq!(hull.vertices(), vec![p(0., 0.), p(4., 0.)]);
+        assert_eq!(hull.area(), 0.);
+        assert_eq!(hull.perimeter(), 8.);
+
+        assert!(hull.insert(p(4., 4.)));
+        assert!(hull.insert(p(0., 4.)));
+        assert_eq!(hull.vertices(), vec![p(0., 0.), p(4., 0.), p(4., 4.), p(0., 4.)]);
+        assert_eq!(hull.len(), 4);
+        assert_eq!(hull.area(), 16.);
+        assert_eq!(hull.perimeter(), 16.);
+
+        // inside and on an edge
+        assert!(!hull.insert(p(1., 3.)));
+        assert!(!hull.insert(p(4., 2.)));
+        // a point, which covers two corners
+        assert!(hull.insert(p(8., 2.)));
+        assert_eq!(hull.vertices(), vec![p(0., 0.), p(4., 0.), p(8., 2.), p(4., 4.), p(0., 4.)]);
+        assert_eq!(hull.area(), 24.);
+        assert!(hull.insert(p(-1., 5.)));
+        assert_eq!(hull.vertices(), vec![p(-1., 5.), p(0., 0.), p(4., 0.), p(8., 2.), p(4., 4.)]);
+    }
+
+    #[test]
+    fn incremental_random_walk() {
+        let mut rng = Pcg64::seed_from_u64(42);
+        let mut walk = vec![Point2::new(1e3, -1e3)];
+        let mut hull = IncrementalHull2d::new();
+        hull.insert(walk[0]);
+
+        for step in 1..=1000 {
+            let p = walk[walk.len()-1] + Point2::new(StandardNormal.sample(&mut rng), StandardNormal.sample(&mut rng));
+            walk.push(p);
+
+            let before = ConvexPolygon::new(hull.vertices());
+            assert_eq!(hull.insert(p), !before.contains(p));
+
+            if step % 250 != 0 {
+                continue
+            }
+            let expected = andrew_points(walk.clone());
+            assert_eq!(hull.vertices(), expected);
+            assert_eq!(hull.len(), expected.len());
+            assert_approx_eq!(hull.area(), area_points(&expected), 1e-9 * hull.area());
+            assert_approx_eq!(hull.perimeter(), perimeter_points(&expected), 1e-9 * hull.perimeter());
+        }
+    }
+}
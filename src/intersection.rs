@@ -0,0 +1,435 @@
+use crate::d2::Point2;
+use crate::d3::{Point3, Facet3};
+use crate::predicates::{orient2d, orient3d};
+use crate::convex::ConvexPolygon;
+use crate::primitives::area_points;
+use crate::quickhull3d::try_quickhull3d;
+
+// O'Rourke, Chien, Olson and Naddor, "A new linear algorithm for intersecting
+// convex polygons", Computer Graphics and Image Processing 19, 384 (1982).
+//
+// One edge of each polygon is advanced at a time, such that both edges chase
+// each other around the polygons. Every crossing of two edges is a vertex
+// of the intersection, as are the vertices of the polygon, which is inside
+// of the other one at that moment. After at most two turns around both
+// polygons all crossings are found in O(n + m).
+//
+// Vertices of one polygon on an edge of the other and collinear edges, which
+// are common for points on a lattice, are handled as in O'Rourke,
+// "Computational Geometry in C", 2nd ed., Cambridge University Press (1998).
+
+/// which polygon is inside of the other at the current crossing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Inside {
+    P,
+    Q,
+    Unknown,
+}
+
+/// how two segments intersect
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Crossing {
+    None,
+    /// a crossing in the interior of both segments
+    Proper(Point2),
+    /// an end point of one segment lies on the other one
+    Vertex(Point2),
+    /// collinear segments, which overlap between both points
+    Overlap(Point2, Point2),
+}
+
+/// whether `c`, which lies on the line through `a` and `b`, is on the segment
+fn between(a: Point2, b: Point2, c: Point2) -> bool {
+    if a.x != b.x {
+        a.x <= c.x && c.x <= b.x || a.x >= c.x && c.x >= b.x
+    } else {
+        a.y <= c.y && c.y <= b.y || a.y >= c.y && c.y >= b.y
+    }
+}
+
+/// overlap of the collinear segments from `a` to `b` and from `c` to `d`
+fn overlap(a: Point2, b: Point2, c: Point2, d: Point2) -> Crossing {
+    let (c_ab, d_ab) = (between(a, b, c), between(a, b, d));
+    let (a_cd, b_cd) = (between(c, d, a), between(c, d, b));
+
+    if c_ab && d_ab {
+        Crossing::Overlap(c, d)
+    } else if a_cd && b_cd {
+        Crossing::Overlap(a, b)
+    } else if c_ab && b_cd {
+        Crossing::Overlap(c, b)
+    } else if c_ab && a_cd {
+        Crossing::Overlap(c, a)
+    } else if d_ab && b_cd {
+        Crossing::Overlap(d, b)
+    } else if d_ab && a_cd {
+        Crossing::Overlap(d, a)
+    } else {
+        Crossing::None
+    }
+}
+
+/// intersection of the segments from `a` to `b` and from `c` to `d`
+fn crossing(a: Point2, b: Point2, c: Point2, d: Point2) -> Crossing {
+    let (o1, o2) = (orient2d(a, b, c), orient2d(a, b, d));
+    let (o3, o4) = (orient2d(c, d, a), orient2d(c, d, b));
+
+    if o1 == 0. && o2 == 0. {
+        return overlap(a, b, c, d)
+    }
+
+    let straddles = |x: f64, y: f64| x <= 0. && y >= 0. || x >= 0. && y <= 0.;
+    if !straddles(o1, o2) || !straddles(o3, o4) {
+        Crossing::None
+    } else if o1 == 0. {
+        Crossing::Vertex(c)
+    } else if o2 == 0. {
+        Crossing::Vertex(d)
+    } else if o3 == 0. {
+        Crossing::Vertex(a)
+    } else if o4 == 0. {
+        Crossing::Vertex(b)
+    } else {
+        Crossing::Proper(a + (b - a) * (o3 / (o3 - o4)))
+    }
+}
+
+/// O'Rourke's edge chasing, the vertices of the intersection in
+/// counter-clockwise order, possibly with repetitions, and whether the
+/// boundaries of the polygons cross at all
+fn chase(p: &[Point2], q: &[Point2]) -> (Vec<Point2>, bool) {
+    let (n, m) = (p.len(), q.len());
+    let mut out = Vec::new();
+
+    let (mut a, mut b) = (0, 0);
+    // number of advances of `a` and `b` since the first crossing
+    let (mut aa, mut ba) = (0, 0);
+    let mut inside = Inside::Unknown;
+
+    loop {
+        let (a1, b1) = ((a + n - 1) % n, (b + m - 1) % m);
+        let edge_a = p[a] - p[a1];
+        let edge_b = q[b] - q[b1];
+
+        let cross = edge_a.cross(edge_b);
+        // `p[a]` left of the edge of `q` and vice versa
+        let a_hb = orient2d(q[b1], q[b], p[a]);
+        let b_ha = orient2d(p[a1], p[a], q[b]);
+
+        match crossing(p[a1], p[a], q[b1], q[b]) {
+            Crossing::Proper(x) | Crossing::Vertex(x) => {
+                if inside == Inside::Unknown && out.is_empty() {
+                    aa = 0;
+                    ba = 0;
+                }
+                out.push(x);
+                if a_hb > 0. {
+                    inside = Inside::P;
+                } else if b_ha > 0. {
+                    inside = Inside::Q;
+                }
+            },
+            // oppositely oriented edges overlap, so the polygons only touch
+            Crossing::Overlap(x, y) if edge_a.dot(edge_b) < 0. => return (vec![x, y], true),
+            _ => (),
+        }
+
+        // advance the edge, which points towards the line through the other
+        // one, or for collinear edges the one outside
+        let advance_a = if cross == 0. && a_hb < 0. && b_ha < 0. {
+            // parallel edges facing away from each other separate the polygons
+            return (Vec::new(), true)
+        } else if cross == 0. && a_hb == 0. && b_ha == 0. {
+            inside != Inside::P
+        } else if cross >= 0. {
+            b_ha > 0.
+        } else {
+            a_hb <= 0.
+        };
+
+        if advance_a {
+            if inside == Inside::P {
+                out.push(p[a]);
+            }
+            a = (a + 1) % n;
+            aa += 1;
+        } else {
+            if inside == Inside::Q {
+                out.push(q[b]);
+            }
+            b = (b + 1) % m;
+            ba += 1;
+        }
+
+        if !((aa < n || ba < m) && aa < 2 * n && ba < 2 * m) {
+            break
+        }
+    }
+
+    (out, inside != Inside::Unknown)
+}
+
+/// removes repeated points and points on an edge of a convex polygon and
+/// rotates it to start at the lexicographically smallest vertex
+fn cleanup(mut polygon: Vec<Point2>) -> Vec<Point2> {
+    polygon.dedup();
+    while polygon.len() > 1 && polygon.first() == polygon.last() {
+        polygon.pop();
+    }
+
+    let lexicographic = |a: &&Point2, b: &&Point2| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y));
+    let (Some(&min), Some(&max)) = (polygon.iter().min_by(lexicographic), polygon.iter().max_by(lexicographic)) else {
+        return polygon
+    };
+    // the polygons only touch in a segment or a point
+    if polygon.iter().all(|&x| orient2d(min, max, x) == 0.) {
+        return if min == max { vec![min] } else { vec![min, max] }
+    }
+
+    let mut out: Vec<Point2> = Vec::with_capacity(polygon.len());
+    for x in polygon {
+        while out.len() >= 2 && orient2d(out[out.len()-2], out[out.len()-1], x) <= 0. {
+            out.pop();
+        }
+        out.push(x);
+    }
+    // the same at the transition from the last to the first vertex
+    loop {
+        let h = out.len();
+        if h > 3 && orient2d(out[h-2], out[h-1], out[0]) <= 0. {
+            out.pop();
+        } else if h > 3 && orient2d(out[h-1], out[0], out[1]) <= 0. {
+            out.remove(0);
+        } else {
+            break
+        }
+    }
+
+    let start = out.iter().position(|&x| x == min).unwrap_or(0);
+    out.rotate_left(start);
+    out
+}
+
+/// a point in the interior of a convex polygon of at least three extreme points
+fn interior(polygon: &[Point2]) -> Point2 {
+    (polygon[0] + polygon[1] + polygon[2]) / 3.
+}
+
+/// intersection of two convex polygons in counter-clockwise order, which
+/// consist of extreme points only, in O(n + m)
+///
+/// the result is again a convex polygon in counter-clockwise order starting
+/// at its lexicographically smallest vertex, which is empty if the polygons
+/// are disjoint and degenerates to a segment or a point if they only touch
+pub fn intersect_polygons(p: &[Point2], q: &[Point2]) -> Vec<Point2> {
+    if p.len() < 3 || q.len() < 3 {
+        return Vec::new()
+    }
+
+    let (out, crossed) = chase(p, q);
+    if crossed {
+        return cleanup(out)
+    }
+
+    // the boundaries do not cross, so either the interiors overlap and one
+    // contains the other or they are disjoint, maybe touching in single points
+    let nested = ConvexPolygon::new(q.to_vec()).contains(interior(p))
+        || ConvexPolygon::new(p.to_vec()).contains(interior(q));
+    if !nested {
+        cleanup(out)
+    } else if area_points(p) <= area_points(q) {
+        cleanup(p.to_vec())
+    } else {
+        cleanup(q.to_vec())
+    }
+}
+
+/// the part of the convex polygon `polygon` behind the plane of `facet`
+fn clip(polygon: &[Point3], facet: &Facet3) -> Vec<Point3> {
+    let [u, v, w] = facet.vertices;
+    let height: Vec<f64> = polygon.iter().map(|&x| orient3d(u, v, w, x)).collect();
+
+    let mut out = Vec::with_capacity(polygon.len() + 1);
+    for i in 0..polygon.len() {
+        let j = (i + 1) % polygon.len();
+        let (x, y) = (polygon[i], polygon[j]);
+        let (hx, hy) = (height[i], height[j]);
+        if hx <= 0. {
+            out.push(x);
+        }
+        // the edge crosses the plane
+        if hx < 0. && hy > 0. || hx > 0. && hy < 0. {
+            out.push(x + (y - x) * (hx / (hx - hy)));
+        }
+    }
+    out
+}
+
+/// the vertices of the facets of `a` clipped to the inside of `b`
+fn clipped_vertices(a: &[Facet3], b: &[Facet3]) -> Vec<Point3> {
+    a.iter()
+        .flat_map(|f| b.iter().fold(f.vertices.to_vec(), |polygon, g| clip(&polygon, g)))
+        .collect()
+}
+
+/// intersection of two 3D hulls as returned by `quickhull3d` in O(F_a F_b)
+/// for hulls of F_a and F_b facets
+///
+/// the cost is quadratic: every facet of each hull is clipped by the plane
+/// of every facet of the other hull, the hull of the remaining vertices is
+/// the intersection, which is empty if the hulls are disjoint or only touch;
+/// the halfspaces are not intersected directly, which would take
+/// O((F_a + F_b) log(F_a + F_b)) by the hull of the dual points, but needs
+/// a point in the interior of the intersection
+pub fn intersect_hulls(a: &[Facet3], b: &[Facet3]) -> Vec<Facet3> {
+    let mut vertices = clipped_vertices(a, b);
+    vertices.extend(clipped_vertices(b, a));
+
+    try_quickhull3d(&vertices).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+    use crate::d2::from_flat;
+    use crate::d3::{volume, Point3};
+    use crate::util::{get_test_vector_2d, get_test_vector_gaussian, get_test_vector_gaussian_3d, is_convex};
+    use crate::util::tests::get_simple_cube;
+    use rand::{Rng, SeedableRng};
+    use rand_pcg::Pcg64;
+    use crate::{andrew_points, quickhull3d};
+
+    /// clips `p` by the halfplanes left of all edges of `q` in O(n m)
+    fn clip_polygon(p: &[Point2], q: &[Point2]) -> Vec<Point2> {
+        let clipped = (0..q.len()).fold(p.to_vec(), |polygon, i| {
+            let (u, v) = (q[i], q[(i + 1) % q.len()]);
+            let height: Vec<f64> = polygon.iter().map(|&x| orient2d(u, v, x)).collect();
+
+            let mut out = Vec::with_capacity(polygon.len() + 1);
+            for j in 0..polygon.len() {
+                let k = (j + 1) % polygon.len();
+                let (x, y) = (polygon[j], polygon[k]);
+                let (hx, hy) = (height[j], height[k]);
+                if hx >= 0. {
+                    out.push(x);
+                }
+                if hx < 0. && hy > 0. || hx > 0. && hy < 0. {
+                    out.push(x + (y - x) * (hx / (hx - hy)));
+                }
+            }
+            out
+        });
+
+        // points on the clipping lines may be repeated or lie on an edge
+        andrew_points(clipped)
+    }
+
+    fn shifted(polygon: &[Point2], x: f64, y: f64) -> Vec<Point2> {
+        polygon.iter().map(|&p| p + Point2::new(x, y)).collect()
+    }
+
+    #[test]
+    fn intersect_squares() {
+        let p = Point2::new;
+        let square = vec![p(0., 0.), p(1., 0.), p(1., 1.), p(0., 1.)];
+
+        let overlap = intersect_polygons(&square, &shifted(&square, 0.5, 0.5));
+        assert_eq!(overlap.len(), 4);
+        assert_approx_eq!(area_points(&overlap), 0.25);
+
+        // the same polygon, nested polygons and collinear edges
+        assert_approx_eq!(area_points(&intersect_polygons(&square, &square)), 1.);
+        let small = vec![p(0.25, 0.25), p(0.75, 0.25), p(0.75, 0.75), p(0.25, 0.75)];
+        assert_eq!(intersect_polygons(&square, &small), small);
+        assert_eq!(intersect_polygons(&small, &square), small);
+        assert_approx_eq!(area_points(&intersect_polygons(&square, &shifted(&square, 0.5, 0.))), 0.5);
+
+        assert!(intersect_polygons(&square, &shifted(&square, 2., 0.5)).is_empty());
+        let touching = intersect_polygons(&square, &shifted(&square, 1., 0.));
+        assert_eq!(area_points(&touching), 0.);
+    }
+
+    #[test]
+    fn intersect_lattice() {
+        let p = Point2::new;
+        // vertices of each polygon lie on the edges of the other
+        let a = vec![p(0., 0.), p(2., 0.), p(2., 2.), p(0., 2.)];
+        let b = vec![p(0., 2.), p(1., 1.), p(3., 0.), p(2., 2.), p(0., 3.)];
+        let expected = vec![p(0., 2.), p(1., 1.), p(2., 0.5), p(2., 2.)];
+
+        assert_eq!(intersect_polygons(&a, &b), expected);
+        assert_eq!(intersect_polygons(&b, &a), expected);
+
+        // collinear edges in the same and in opposite directions, a common vertex
+        assert_eq!(intersect_polygons(&a, &shifted(&a, 1., 0.)), vec![p(1., 0.), p(2., 0.), p(2., 2.), p(1., 2.)]);
+        assert_eq!(intersect_polygons(&a, &shifted(&a, 2., 1.)), vec![p(2., 1.), p(2., 2.)]);
+        assert_eq!(intersect_polygons(&a, &shifted(&a, 2., 2.)), vec![p(2., 2.)]);
+        let c = vec![p(2., 2.), p(4., 0.), p(4., 4.)];
+        assert_eq!(intersect_polygons(&a, &c), vec![p(2., 2.)]);
+        assert_eq!(intersect_polygons(&c, &a), vec![p(2., 2.)]);
+    }
+
+    #[test]
+    fn intersect_lattice_brute_force() {
+        let mut rng = Pcg64::seed_from_u64(42);
+        let mut polygon = |k: usize| -> Vec<Point2> {
+            andrew_points((0..k).map(|_| Point2::new(rng.gen_range(0..6) as f64, rng.gen_range(0..6) as f64)).collect::<Vec<_>>())
+        };
+
+        for k in 0..500 {
+            let (a, b) = (polygon(3 + k % 7), polygon(3 + k % 5));
+            if a.len() < 3 || b.len() < 3 {
+                continue
+            }
+            let c = intersect_polygons(&a, &b);
+            let expected = clip_polygon(&a, &b);
+            assert_approx_eq!(area_points(&c), area_points(&expected));
+            assert_eq!(c.len(), expected.len());
+        }
+    }
+
+    #[test]
+    fn intersect_random() {
+        let a = andrew_points(from_flat(&get_test_vector_gaussian(200)));
+        let b = andrew_points(from_flat(&get_test_vector_2d(200)));
+
+        for (x, y) in [(0., 0.), (0.3, 0.1), (-0.2, 0.4), (0.7, -0.6), (2., 0.)] {
+            let b = shifted(&b, x, y);
+            let c = intersect_polygons(&a, &b);
+            let d = intersect_polygons(&b, &a);
+
+            let expected = clip_polygon(&a, &b);
+
+            assert_approx_eq!(area_points(&c), area_points(&expected));
+            assert_approx_eq!(area_points(&d), area_points(&expected));
+            if !c.is_empty() {
+                assert!(c.len() >= 3);
+            }
+        }
+    }
+
+    #[test]
+    fn intersect_cubes() {
+        let cube = quickhull3d(&get_simple_cube());
+        let shift = |x: f64, y: f64, z: f64| -> Vec<Facet3> {
+            let v: Vec<Point3> = get_simple_cube().into_iter().map(|p| p + Point3::new(x, y, z)).collect();
+            quickhull3d(&v)
+        };
+
+        let overlap = intersect_hulls(&cube, &shift(0.5, 0.5, 0.5));
+        assert_approx_eq!(volume(&overlap), 0.125);
+        assert_approx_eq!(volume(&intersect_hulls(&cube, &cube)), 1.);
+        assert_approx_eq!(volume(&intersect_hulls(&cube, &shift(0.5, 0., 0.))), 0.5);
+        assert!(intersect_hulls(&cube, &shift(2., 0., 0.)).is_empty());
+        assert!(intersect_hulls(&cube, &shift(1., 0., 0.)).is_empty());
+
+        let a = quickhull3d(&get_test_vector_gaussian_3d(200));
+        let v: Vec<Point3> = get_test_vector_gaussian_3d(300).into_iter().skip(100).map(|p| p + Point3::new(10., 0., 0.)).collect();
+        let b = quickhull3d(&v);
+        let c = intersect_hulls(&a, &b);
+        assert!(volume(&c) > 0.);
+        assert!(volume(&c) < volume(&a).min(volume(&b)));
+        assert!(is_convex(&c, &c.iter().flat_map(|f| f.vertices).collect::<Vec<_>>()));
+    }
+}
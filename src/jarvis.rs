@@ -1,51 +1,78 @@
-use itertools::Itertools;
+use std::cmp::Ordering::Less;
 
-use crate::primitives::{cross2d, dist2};
+use crate::d2::{Point2, expect_flat, try_from_flat, to_flat, gather};
+use crate::error::{HullError, validate_2d};
+use crate::boundary::{Boundary, insert_boundary_points};
+use crate::predicates::{orient2d, cmp_dist2};
 
-#[cfg(not(feature = "visual"))]
+// points stores a contiguous array of 2N floats in the format x1, y1, x2, y2, ...
+// an odd number of values panics
 pub fn jarvis(pointset: &[f64]) -> Vec<f64> {
-    if pointset.len() < 3*2 {
-        return pointset.to_vec()
+    to_flat(&jarvis_points(expect_flat(pointset, "jarvis")))
+}
+
+// points stores a contiguous array of 2N floats in the format x1, y1, x2, y2, ...
+pub fn try_jarvis(pointset: &[f64]) -> Result<Vec<f64>, HullError> {
+    let pointset = try_from_flat(pointset)?;
+    validate_2d(&pointset)?;
+    Ok(to_flat(&gather(&pointset, &hull(&pointset))))
+}
+
+pub fn jarvis_points<P: Into<Point2>>(pointset: impl IntoIterator<Item = P>) -> Vec<Point2> {
+    let pointset: Vec<Point2> = pointset.into_iter().map(Into::into).collect();
+    gather(&pointset, &hull(&pointset))
+}
+
+/// indices of the hull vertices in counter-clockwise order
+pub fn jarvis_indices(pointset: &[Point2]) -> Vec<usize> {
+    hull(pointset)
+}
+
+/// like `jarvis_indices`, but with `Boundary::All` the points on the edges are kept
+pub fn jarvis_indices_with(pointset: &[Point2], boundary: Boundary) -> Vec<usize> {
+    match boundary {
+        Boundary::Extreme => hull(pointset),
+        Boundary::All => insert_boundary_points(pointset, hull(pointset)),
     }
+}
 
-    let start = (pointset[0], pointset[1]);
-    let min = pointset.iter()
-        .tuples::<(_, _)>()
-        .fold(start, |min, (&x, &y)| {
-            if x < min.0 { (x, y) } else { min }
+#[cfg(not(feature = "visual"))]
+fn hull(pointset: &[Point2]) -> Vec<usize> {
+    if pointset.len() < 3 {
+        return (0..pointset.len()).collect()
+    }
+
+    let min = (0..pointset.len())
+        .fold(0, |min, i| {
+            // lexicographic, such that the start is a corner
+            if (pointset[i].x, pointset[i].y) < (pointset[min].x, pointset[min].y) { i } else { min }
         });
 
-    let mut hull = Vec::new();
-    hull.push(min.0);
-    hull.push(min.1);
+    let mut hull = vec![min];
 
-    let mut p = pointset.iter()
-        .cloned()
-        .tuples::<(_, _)>()
-        .find(|&i| i != min)
-        .unwrap();
+    // all points coincide
+    let mut p = match (0..pointset.len()).find(|&i| pointset[i] != pointset[min]) {
+        Some(p) => p,
+        None => return hull,
+    };
 
     loop {
-        for i in pointset.iter()
-            .cloned()
-            .tuples::<(_, _)>()
-        {
-            let a = (hull[hull.len()-2], hull[hull.len()-1]);
-            let orientation = cross2d(a, i, p);
+        for i in 0..pointset.len() {
+            let a = pointset[hull[hull.len()-1]];
+            let orientation = orient2d(a, pointset[i], pointset[p]);
             if orientation > 0f64 {
                 p = i;
             } else if orientation == 0f64 {
                 // take the one furthest away, to avoid collinear points
-                if dist2(a, p) < dist2(a, i) {
+                if cmp_dist2(a, pointset[p], pointset[i]) == Less {
                     p = i;
                 }
             }
         }
-        if p == min {
+        if pointset[p] == pointset[min] {
             break;
         }
-        hull.push(p.0);
-        hull.push(p.1);
+        hull.push(p);
     }
 
     hull
@@ -55,43 +82,41 @@ pub fn jarvis(pointset: &[f64]) -> Vec<f64> {
 use crate::visualization::SVG;
 
 #[cfg(feature = "visual")]
-pub fn jarvis(pointset: &[f64]) -> Vec<f64> {
-    let start = (pointset[0], pointset[1]);
-    let min = pointset.iter()
-        .tuples::<(_, _)>()
-        .fold(start, |min, (&x, &y)| {
-            if x < min.0 { (x, y) } else { min }
+fn hull(pointset: &[Point2]) -> Vec<usize> {
+    if pointset.len() < 3 {
+        return (0..pointset.len()).collect()
+    }
+
+    let min = (0..pointset.len())
+        .fold(0, |min, i| {
+            // lexicographic, such that the start is a corner
+            if (pointset[i].x, pointset[i].y) < (pointset[min].x, pointset[min].y) { i } else { min }
         });
 
-    let mut hull = Vec::new();
-    hull.push(min.0);
-    hull.push(min.1);
+    let mut hull = vec![min];
 
-    let mut p = pointset.iter()
-        .cloned()
-        .tuples::<(_, _)>()
-        .find(|&i| i != min)
-        .unwrap();
+    // all points coincide
+    let mut p = match (0..pointset.len()).find(|&i| pointset[i] != pointset[min]) {
+        Some(p) => p,
+        None => return hull,
+    };
 
     let mut k = 0;
     loop {
-        for i in pointset.iter()
-            .cloned()
-            .tuples::<(_, _)>()
-        {
-            let a = (hull[hull.len()-2], hull[hull.len()-1]);
-            let orientation = cross2d(a, i, p);
+        for i in 0..pointset.len() {
+            let a = pointset[hull[hull.len()-1]];
+            let orientation = orient2d(a, pointset[i], pointset[p]);
 
             if orientation >= 0f64 {
                 k += 1;
                 let filename = format!("img/jarvis_{:04}.svg", k);
                 let mut s = SVG::new();
                 s.points(pointset, "grey");
-                s.points(&hull, "black");
-                s.lines(&hull, "black");
-                s.lines(&[p.0, p.1, a.0, a.1], "green");
-                s.points(&[p.0, p.1], "green");
-                s.points(&[i.0, i.1], "red");
+                s.points(&gather(pointset, &hull), "black");
+                s.lines(&gather(pointset, &hull), "black");
+                s.lines(&[pointset[p], a], "green");
+                s.points(&[pointset[p]], "green");
+                s.points(&[pointset[i]], "red");
                 s.save(&filename).expect("io error");
             }
 
@@ -99,16 +124,15 @@ pub fn jarvis(pointset: &[f64]) -> Vec<f64> {
                 p = i;
             } else if orientation == 0f64 {
                 // take the one furthest away, to avoid collinear points
-                if dist2(a, p) < dist2(a, i) {
+                if cmp_dist2(a, pointset[p], pointset[i]) == Less {
                     p = i;
                 }
             }
         }
-        if p == min {
+        if pointset[p] == pointset[min] {
             break;
         }
-        hull.push(p.0);
-        hull.push(p.1);
+        hull.push(p);
 
     }
 
@@ -116,8 +140,8 @@ pub fn jarvis(pointset: &[f64]) -> Vec<f64> {
     let filename = format!("img/jarvis_{:04}.svg", k);
     let mut s = SVG::new();
     s.points(pointset, "grey");
-    s.points(&hull, "black");
-    s.polygon(&hull, "black");
+    s.points(&gather(pointset, &hull), "black");
+    s.polygon(&gather(pointset, &hull), "black");
     s.save(&filename).expect("io error");
 
     hull
@@ -126,25 +150,37 @@ pub fn jarvis(pointset: &[f64]) -> Vec<f64> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{util::tests::{check_2048, check_square}, akl};
+    use crate::{util::tests::{check_2048, check_square, check_square_indices, check_square_boundary}};
+    use crate::{Jarvis, Akl};
 
     #[test]
     fn jarvis_square() {
-        check_square(jarvis);
+        check_square(&Jarvis);
     }
 
     #[test]
     fn jarvis_2048() {
-        check_2048(jarvis, "jarvis");
+        check_2048(&Jarvis);
     }
 
     #[test]
     fn jarvis_akl_square() {
-        check_square(|v| jarvis(&akl(v)));
+        check_square(&Akl(Jarvis));
     }
 
     #[test]
     fn jarvis_akl_2048() {
-        check_2048(|v| jarvis(&akl(v)), "jarvis_akl");
+        check_2048(&Akl(Jarvis));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn jarvis_square_boundary() {
+        check_square_boundary(&Jarvis);
+        check_square_boundary(&Akl(Jarvis));
+    }
+
+    #[test]
+    fn jarvis_indices_square() {
+        check_square_indices(jarvis_indices);
+    }
+}
@@ -0,0 +1,404 @@
+use std::cmp::Ordering::{self, Less, Equal, Greater};
+
+use crate::d2::{Point2, expect_flat, try_from_flat, to_flat, gather};
+use crate::error::{HullError, validate_2d};
+use crate::boundary::{Boundary, insert_boundary_points};
+use crate::predicates::{orient2d, cmp_orient2d, cmp_slopes};
+
+// Kirkpatrick and Seidel, "The Ultimate Planar Convex Hull Algorithm?",
+// SIAM Journal on Computing 15, 287 (1986).
+
+// points stores a contiguous array of 2N floats in the format x1, y1, x2, y2, ...
+// an odd number of values panics
+pub fn kirkpatrick(pointset: &[f64]) -> Vec<f64> {
+    to_flat(&kirkpatrick_points(expect_flat(pointset, "kirkpatrick")))
+}
+
+// points stores a contiguous array of 2N floats in the format x1, y1, x2, y2, ...
+pub fn try_kirkpatrick(pointset: &[f64]) -> Result<Vec<f64>, HullError> {
+    let pointset = try_from_flat(pointset)?;
+    validate_2d(&pointset)?;
+    Ok(to_flat(&gather(&pointset, &hull(&pointset))))
+}
+
+pub fn kirkpatrick_points<P: Into<Point2>>(pointset: impl IntoIterator<Item = P>) -> Vec<Point2> {
+    let pointset: Vec<Point2> = pointset.into_iter().map(Into::into).collect();
+    gather(&pointset, &hull(&pointset))
+}
+
+/// indices of the hull vertices in counter-clockwise order
+pub fn kirkpatrick_indices(pointset: &[Point2]) -> Vec<usize> {
+    hull(pointset)
+}
+
+/// like `kirkpatrick_indices`, but with `Boundary::All` the points on the edges are kept
+pub fn kirkpatrick_indices_with(pointset: &[Point2], boundary: Boundary) -> Vec<usize> {
+    match boundary {
+        Boundary::Extreme => hull(pointset),
+        Boundary::All => insert_boundary_points(pointset, hull(pointset)),
+    }
+}
+
+/// the `k`-th smallest element in O(n) with the median of medians as pivot
+fn select<T: Copy>(v: &[T], k: usize, cmp: &impl Fn(&T, &T) -> Ordering) -> T {
+    if v.len() <= 10 {
+        let mut v = v.to_vec();
+        v.sort_unstable_by(cmp);
+        return v[k]
+    }
+
+    let medians: Vec<T> = v.chunks(5)
+        .map(|c| select(c, c.len() / 2, cmp))
+        .collect();
+    let pivot = select(&medians, medians.len() / 2, cmp);
+
+    let less: Vec<T> = v.iter().cloned().filter(|x| cmp(x, &pivot) == Less).collect();
+    let greater: Vec<T> = v.iter().cloned().filter(|x| cmp(x, &pivot) == Greater).collect();
+    let equal = v.len() - less.len() - greater.len();
+
+    if k < less.len() {
+        select(&less, k, cmp)
+    } else if k < less.len() + equal {
+        pivot
+    } else {
+        select(&greater, k - less.len() - equal, cmp)
+    }
+}
+
+/// the edge of the upper hull of `candidates` which crosses the vertical
+/// line between the points `left_of` and the others, in O(n) by prune and search
+fn bridge(pointset: &[Point2], candidates: &[usize], left_of: &impl Fn(usize) -> bool) -> (usize, usize) {
+    let mut candidates = candidates.to_vec();
+
+    loop {
+        if candidates.len() == 2 {
+            let (p, q) = (candidates[0], candidates[1]);
+            return if pointset[p].x < pointset[q].x { (p, q) } else { (q, p) }
+        }
+
+        // pair up the points, of vertical pairs only the upper can be on the bridge
+        let mut next = Vec::new();
+        let mut pairs = Vec::new();
+        for c in candidates.chunks(2) {
+            match *c {
+                [p, q] => {
+                    let (p, q) = if pointset[p].x <= pointset[q].x { (p, q) } else { (q, p) };
+                    if pointset[p].x < pointset[q].x {
+                        pairs.push((p, q));
+                    } else if pointset[p].y > pointset[q].y {
+                        next.push(p);
+                    } else {
+                        next.push(q);
+                    }
+                }
+                [p] => next.push(p),
+                _ => unreachable!(),
+            }
+        }
+        if pairs.is_empty() {
+            candidates = next;
+            continue
+        }
+
+        let slope = |a: &(usize, usize), b: &(usize, usize)| {
+            cmp_slopes(pointset[a.0], pointset[a.1], pointset[b.0], pointset[b.1])
+        };
+        let median = select(&pairs, pairs.len() / 2, &slope);
+        let (a, b) = (pointset[median.0], pointset[median.1]);
+
+        // the leftmost and rightmost point touched by the supporting line of median slope
+        let mut top = candidates[0];
+        let mut min = top;
+        let mut max = top;
+        for &i in candidates.iter() {
+            match cmp_orient2d(a, b, pointset[top], pointset[i]) {
+                Less => {
+                    top = i;
+                    min = i;
+                    max = i;
+                }
+                Equal => {
+                    if pointset[i].x < pointset[min].x { min = i; }
+                    if pointset[i].x > pointset[max].x { max = i; }
+                }
+                Greater => {}
+            }
+        }
+
+        if left_of(min) && !left_of(max) {
+            return (min, max)
+        }
+
+        // the bridge is less steep than the median if the supporting line touches left of the split,
+        // so the left point of every steeper pair lies below it, and vice versa
+        let touches_left = left_of(max);
+        for pair in pairs.iter() {
+            match (slope(pair, &median), touches_left) {
+                (Less, true) | (Greater, false) => {
+                    next.push(pair.0);
+                    next.push(pair.1);
+                }
+                (_, true) => next.push(pair.1),
+                (_, false) => next.push(pair.0),
+            }
+        }
+        candidates = next;
+    }
+}
+
+/// the vertical line at the median `x` of `candidates`, which separates at least one of them
+fn split<'a>(pointset: &'a [Point2], candidates: &[usize]) -> impl Fn(usize) -> bool + 'a {
+    let xs: Vec<f64> = candidates.iter().map(|&i| pointset[i].x).collect();
+    let median = select(&xs, xs.len() / 2, &|a: &f64, b: &f64| a.total_cmp(b));
+
+    // if the median is the largest value, the points on the line go to the right
+    let inclusive = xs.iter().any(|&x| x > median);
+    move |i: usize| if inclusive { pointset[i].x <= median } else { pointset[i].x < median }
+}
+
+/// the leftmost and rightmost points, the upper ones on ties
+fn extrema(pointset: &[Point2]) -> (usize, usize) {
+    (0..pointset.len())
+        .fold((0, 0), |(min, max), i| {
+            let (p, pmin, pmax) = (pointset[i], pointset[min], pointset[max]);
+            let min = if (p.x, -p.y) < (pmin.x, -pmin.y) { i } else { min };
+            let max = if (p.x, p.y) > (pmax.x, pmax.y) { i } else { max };
+            (min, max)
+        })
+}
+
+/// vertices of the lower hull from left to right followed by the upper hull
+/// from right to left, without repeating the points they share
+fn join(pointset: &[Point2], lower: Vec<usize>, upper: Vec<usize>) -> Vec<usize> {
+    let mut hull = lower;
+    let mut upper = upper.into_iter().rev().peekable();
+    if let (Some(&l), Some(&u)) = (hull.last(), upper.peek()) {
+        if pointset[l] == pointset[u] {
+            upper.next();
+        }
+    }
+    hull.extend(upper);
+    if hull.len() > 1 && pointset[hull[0]] == pointset[hull[hull.len()-1]] {
+        hull.pop();
+    }
+    hull
+}
+
+#[cfg(not(feature = "visual"))]
+fn hull(pointset: &[Point2]) -> Vec<usize> {
+    if pointset.len() < 3 {
+        return (0..pointset.len()).collect()
+    }
+
+    // the lower hull is the upper hull of the point reflected at the origin
+    let reflected: Vec<Point2> = pointset.iter().map(|&p| -p).collect();
+    let mut lower = upper_hull(&reflected);
+    lower.reverse();
+
+    join(pointset, lower, upper_hull(pointset))
+}
+
+/// indices of the vertices of the upper hull from left to right
+#[cfg(not(feature = "visual"))]
+fn upper_hull(pointset: &[Point2]) -> Vec<usize> {
+    let (min, max) = extrema(pointset);
+    if pointset[min].x == pointset[max].x {
+        return vec![max]
+    }
+
+    let (a, b) = (pointset[min], pointset[max]);
+    let candidates: Vec<usize> = (0..pointset.len())
+        .filter(|&i| i == min || i == max || orient2d(a, b, pointset[i]) > 0f64)
+        .collect();
+
+    let mut out = vec![min];
+    connect(pointset, &candidates, min, max, &mut out);
+
+    out
+}
+
+/// appends the upper hull of `candidates` after `k` up to and including `m`
+#[cfg(not(feature = "visual"))]
+fn connect(pointset: &[Point2], candidates: &[usize], k: usize, m: usize, out: &mut Vec<usize>) {
+    if candidates.len() == 2 {
+        out.push(m);
+        return
+    }
+
+    let (i, j) = bridge(pointset, candidates, &split(pointset, candidates));
+
+    // marriage before conquest: only the points above the new edges remain
+    if i != k {
+        let (pk, pi) = (pointset[k], pointset[i]);
+        let left: Vec<usize> = candidates.iter()
+            .cloned()
+            .filter(|&p| p == k || p == i || orient2d(pk, pi, pointset[p]) > 0f64)
+            .collect();
+        connect(pointset, &left, k, i, out);
+    }
+    if j != m {
+        let (pj, pm) = (pointset[j], pointset[m]);
+        let right: Vec<usize> = candidates.iter()
+            .cloned()
+            .filter(|&p| p == j || p == m || orient2d(pj, pm, pointset[p]) > 0f64)
+            .collect();
+        out.push(j);
+        connect(pointset, &right, j, m, out);
+    } else {
+        out.push(m);
+    }
+}
+
+#[cfg(feature = "visual")]
+use crate::visualization::SVG;
+
+#[cfg(feature = "visual")]
+fn hull(pointset: &[Point2]) -> Vec<usize> {
+    if pointset.len() < 3 {
+        return (0..pointset.len()).collect()
+    }
+
+    let mut ctr = 0;
+    let mut bridges: Vec<[Point2; 2]> = Vec::new();
+
+    // the lower hull is the upper hull of the point reflected at the origin
+    let reflected: Vec<Point2> = pointset.iter().map(|&p| -p).collect();
+    let mut lower = upper_hull(&reflected, pointset, &mut bridges, &mut ctr);
+    lower.reverse();
+
+    let upper = upper_hull(pointset, pointset, &mut bridges, &mut ctr);
+    let hull = join(pointset, lower, upper);
+
+    ctr += 1;
+    let filename = format!("img/kirkpatrick_{:04}.svg", ctr);
+    let mut s = SVG::new();
+    s.points(pointset, "grey");
+    s.points(&gather(pointset, &hull), "black");
+    s.polygon(&gather(pointset, &hull), "black");
+    s.save(&filename).expect("io error");
+
+    hull
+}
+
+/// indices of the vertices of the upper hull from left to right
+#[cfg(feature = "visual")]
+fn upper_hull(pointset: &[Point2], original: &[Point2], bridges: &mut Vec<[Point2; 2]>, ctr: &mut u32) -> Vec<usize> {
+    let (min, max) = extrema(pointset);
+    if pointset[min].x == pointset[max].x {
+        return vec![max]
+    }
+
+    let (a, b) = (pointset[min], pointset[max]);
+    let candidates: Vec<usize> = (0..pointset.len())
+        .filter(|&i| i == min || i == max || orient2d(a, b, pointset[i]) > 0f64)
+        .collect();
+
+    let mut out = vec![min];
+    connect(pointset, &candidates, min, max, &mut out, original, bridges, ctr);
+
+    out
+}
+
+/// appends the upper hull of `candidates` after `k` up to and including `m`
+#[cfg(feature = "visual")]
+#[allow(clippy::too_many_arguments)]
+fn connect(pointset: &[Point2], candidates: &[usize], k: usize, m: usize, out: &mut Vec<usize>, original: &[Point2], bridges: &mut Vec<[Point2; 2]>, ctr: &mut u32) {
+    if candidates.len() == 2 {
+        out.push(m);
+        return
+    }
+
+    let (i, j) = bridge(pointset, candidates, &split(pointset, candidates));
+
+    *ctr += 1;
+    let filename = format!("img/kirkpatrick_{:04}.svg", ctr);
+    let mut s = SVG::new();
+    s.points(original, "lightgray");
+    s.points(&gather(original, candidates), "green");
+    for l in bridges.iter() {
+        s.lines(l, "grey");
+    }
+    let l = [original[i], original[j]];
+    s.lines(&l, "red");
+    s.points(&l, "red");
+    s.save(&filename).expect("io error");
+    bridges.push(l);
+
+    // marriage before conquest: only the points above the new edges remain
+    if i != k {
+        let (pk, pi) = (pointset[k], pointset[i]);
+        let left: Vec<usize> = candidates.iter()
+            .cloned()
+            .filter(|&p| p == k || p == i || orient2d(pk, pi, pointset[p]) > 0f64)
+            .collect();
+        connect(pointset, &left, k, i, out, original, bridges, ctr);
+    }
+    if j != m {
+        let (pj, pm) = (pointset[j], pointset[m]);
+        let right: Vec<usize> = candidates.iter()
+            .cloned()
+            .filter(|&p| p == j || p == m || orient2d(pj, pm, pointset[p]) > 0f64)
+            .collect();
+        out.push(j);
+        connect(pointset, &right, j, m, out, original, bridges, ctr);
+    } else {
+        out.push(m);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{util::tests::{check_2048, check_square, check_square_indices, check_square_boundary}};
+    use crate::{Kirkpatrick, Akl};
+
+    #[test]
+    fn kirkpatrick_square() {
+        check_square(&Kirkpatrick);
+    }
+
+    #[test]
+    fn kirkpatrick_2048() {
+        check_2048(&Kirkpatrick);
+    }
+
+    #[test]
+    fn kirkpatrick_akl_square() {
+        check_square(&Akl(Kirkpatrick));
+    }
+
+    #[test]
+    fn kirkpatrick_akl_2048() {
+        check_2048(&Akl(Kirkpatrick));
+    }
+
+    #[test]
+    fn kirkpatrick_square_boundary() {
+        check_square_boundary(&Kirkpatrick);
+        check_square_boundary(&Akl(Kirkpatrick));
+    }
+
+    #[test]
+    fn kirkpatrick_indices_square() {
+        check_square_indices(kirkpatrick_indices);
+    }
+
+    #[test]
+    fn kirkpatrick_duplicates() {
+        // many points on the vertical line through the median and duplicated corners
+        let mut v: Vec<Point2> = (0..50).map(|i| Point2::new(0., i as f64 / 50.)).collect();
+        v.extend([Point2::new(-1., 0.5), Point2::new(1., 0.5), Point2::new(1., 0.5), Point2::new(-1., 0.5)]);
+        v.extend((0..50).map(|i| Point2::new(0.5, i as f64 / 100.)));
+
+        let hull = kirkpatrick_points(&v);
+        assert_eq!(hull, crate::andrew_points(&v));
+    }
+
+    #[test]
+    fn select_median() {
+        let v: Vec<usize> = (0..101).map(|i| (i * 37) % 101).collect();
+        for k in [0, 3, 50, 100] {
+            assert_eq!(select(&v, k, &|a: &usize, b: &usize| a.cmp(b)), k);
+        }
+    }
+}
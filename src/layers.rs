@@ -0,0 +1,344 @@
+use std::collections::HashMap;
+use std::iter::once;
+
+use crate::d2::Point2;
+use crate::d3::Point3;
+use crate::boundary::Boundary;
+use crate::error::validate_3d;
+use crate::predicates::orient2d;
+use crate::primitives::all_collinear;
+use crate::quickhull3d::quickhull3d_boundary;
+use crate::convex::ConvexPolytope;
+
+/// maximum number of points of the previous layer used as directions
+const SAMPLE: usize = 32;
+
+// The convex layers or onion decomposition of a pointset are obtained by
+// removing all points on the boundary of the hull and repeating this for the
+// remaining points. The depth of a point is the number of layers around it,
+// which is a robust measure of centrality, the points of the innermost layer
+// are a multivariate median.
+//
+// In 2D the points are sorted only once, every layer is then peeled in
+// linear time by the monotone chain of Andrew's algorithm, such that the
+// runtime is O(n log n + n L) for L layers.
+//
+// In 3D every layer is a hull of the remaining points, which costs
+// O(L n log n) in the expected case. Before each hull, the points extreme
+// in a few directions, towards a sample of the previous layer, span a
+// polytope inside of the next layer. The points strictly inside of it are
+// skipped, like in the heuristic of Akl and Toussaint, such that only the
+// points close to the next layer are passed to `quickhull3d`.
+
+/// the convex layers of a pointset
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConvexLayers {
+    /// indices of the points on every layer from the outside in, in 2D in
+    /// counter-clockwise order, in 3D ascending
+    pub layers: Vec<Vec<usize>>,
+    /// for every point the index of its layer, 0 for the hull
+    pub depth: Vec<usize>,
+}
+
+impl ConvexLayers {
+    fn new(n: usize) -> ConvexLayers {
+        ConvexLayers {
+            layers: Vec::new(),
+            depth: vec![0; n],
+        }
+    }
+
+    /// appends a layer, duplicates of its points belong to it as well
+    fn push(&mut self, layer: &[usize], copies: &[Vec<usize>]) {
+        let layer: Vec<usize> = layer.iter()
+            .flat_map(|&i| once(i).chain(copies[i].iter().cloned()))
+            .collect();
+        for &i in &layer {
+            self.depth[i] = self.layers.len();
+        }
+        self.layers.push(layer);
+    }
+}
+
+/// the hull of the lexicographically `sorted` points including the points
+/// on its edges in counter-clockwise order
+fn chain(pointset: &[Point2], sorted: &[usize]) -> Vec<usize> {
+    let mut hull: Vec<usize> = Vec::new();
+    let turn = |hull: &[usize], i: usize| {
+        let k = hull.len();
+        orient2d(pointset[hull[k-2]], pointset[hull[k-1]], pointset[i])
+    };
+
+    for &i in sorted.iter() {
+        while hull.len() >= 2 && Boundary::All.drops(turn(&hull, i)) {
+            hull.pop();
+        }
+        hull.push(i);
+    }
+    let t = hull.len() + 1;
+    // the last point is already the start of the upper chain
+    for &i in sorted.iter().rev().skip(1) {
+        while hull.len() >= t && Boundary::All.drops(turn(&hull, i)) {
+            hull.pop();
+        }
+        hull.push(i);
+    }
+    // the first point is repeated at the end
+    hull.pop();
+
+    hull
+}
+
+/// convex layers of a 2D pointset, points on an edge of a layer belong to it
+///
+/// the innermost layer may degenerate to a segment or a single point, whose
+/// points are then in lexicographic order
+pub fn convex_layers(pointset: &[Point2]) -> ConvexLayers {
+    let n = pointset.len();
+    let mut sorted: Vec<usize> = (0..n).collect();
+    sorted.sort_by(|&a, &b| {
+        let (a, b) = (pointset[a], pointset[b]);
+        a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y))
+    });
+
+    // duplicates are peeled together with their first occurrence
+    let mut copies = vec![Vec::new(); n];
+    sorted.dedup_by(|a, b| {
+        let duplicate = pointset[*a] == pointset[*b];
+        if duplicate {
+            copies[*b].push(*a);
+        }
+        duplicate
+    });
+
+    let mut layers = ConvexLayers::new(n);
+    let mut peeled = vec![false; n];
+    while !sorted.is_empty() {
+        let layer = if sorted.len() < 3 || all_collinear(pointset, &sorted) {
+            sorted.clone()
+        } else {
+            chain(pointset, &sorted)
+        };
+
+        for &i in &layer {
+            peeled[i] = true;
+        }
+        // removing the layer keeps the remaining points sorted
+        sorted.retain(|&i| !peeled[i]);
+        layers.push(&layer, &copies);
+    }
+
+    layers
+}
+
+/// the `remaining` points, which are not strictly inside of the hull of
+/// their extreme points in `directions`, i.e., the candidates for the next layer
+fn prune(pointset: &[Point3], remaining: &[usize], directions: &[Point3]) -> Vec<usize> {
+    let mut extremes: Vec<usize> = directions.iter()
+        .map(|d| *remaining.iter().max_by(|&&i, &&j| d.dot(pointset[i]).total_cmp(&d.dot(pointset[j]))).unwrap())
+        .collect();
+    extremes.sort_unstable();
+    extremes.dedup();
+
+    let corners: Vec<Point3> = extremes.iter().map(|&i| pointset[i]).collect();
+    if validate_3d(&corners).is_err() {
+        return remaining.to_vec()
+    }
+    let inner = ConvexPolytope::from_points(&corners);
+    remaining.iter()
+        .cloned()
+        .filter(|&i| !inner.contains(pointset[i]) || inner.on_boundary(pointset[i]))
+        .collect()
+}
+
+/// convex layers of a 3D pointset, points on a facet of a layer belong to it
+///
+/// every layer is peeled by `quickhull3d`, the innermost layer may
+/// degenerate to a planar set, a segment or a single point
+pub fn convex_layers_3d(pointset: &[Point3]) -> ConvexLayers {
+    let n = pointset.len();
+
+    // duplicates are peeled together with their first occurrence
+    let mut copies = vec![Vec::new(); n];
+    let mut first: HashMap<[u64; 3], usize> = HashMap::new();
+    let mut remaining = Vec::new();
+    for (i, p) in pointset.iter().enumerate() {
+        let key = [p.x.to_bits(), p.y.to_bits(), p.z.to_bits()];
+        match first.get(&key) {
+            Some(&j) => copies[j].push(i),
+            None => {
+                first.insert(key, i);
+                remaining.push(i);
+            }
+        }
+    }
+
+    // the axes and diagonals, which suffice for the first layer
+    let mut axes = Vec::new();
+    for x in -1..=1 {
+        for y in -1..=1 {
+            for z in -1..=1 {
+                if (x, y, z) != (0, 0, 0) {
+                    axes.push(Point3::new(x as f64, y as f64, z as f64));
+                }
+            }
+        }
+    }
+
+    let mut layers = ConvexLayers::new(n);
+    let mut peeled = vec![false; n];
+    let mut directions = axes.clone();
+    while !remaining.is_empty() {
+        let candidates = prune(pointset, &remaining, &directions);
+        let subset: Vec<Point3> = candidates.iter().map(|&i| pointset[i]).collect();
+        let layer: Vec<usize> = if validate_3d(&subset).is_ok() {
+            quickhull3d_boundary(&subset, Boundary::All).into_iter()
+                .map(|k| candidates[k])
+                .collect()
+        } else {
+            remaining.clone()
+        };
+
+        for &i in &layer {
+            peeled[i] = true;
+        }
+        remaining.retain(|&i| !peeled[i]);
+
+        // the next layer is similar in shape to this one
+        let centre = layer.iter().fold(Point3::new(0., 0., 0.), |c, &i| c + pointset[i]) / layer.len() as f64;
+        directions = axes.clone();
+        directions.extend(layer.iter()
+            .step_by(layer.len().div_ceil(SAMPLE))
+            .map(|&i| pointset[i] - centre));
+
+        layers.push(&layer, &copies);
+    }
+
+    layers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::d2::{from_flat, gather};
+    use crate::util::{get_test_vector_gaussian, get_test_vector_gaussian_3d};
+    use crate::util::tests::{get_simple_cube, get_random_lattice_3d};
+    use crate::predicates::orient3d;
+    use crate::quickhull3d::try_quickhull3d;
+    use crate::andrew::andrew_indices_with;
+
+    fn sorted(mut v: Vec<usize>) -> Vec<usize> {
+        v.sort_unstable();
+        v
+    }
+
+    #[test]
+    fn layers_squares() {
+        let p = Point2::new;
+        let points = vec![
+            p(0., 0.), p(4., 0.), p(4., 4.), p(0., 4.),
+            // on an edge of the hull
+            p(2., 0.),
+            p(1., 1.), p(3., 1.), p(3., 3.), p(1., 3.),
+            // centre and a duplicate of a corner
+            p(2., 2.), p(3., 3.),
+        ];
+
+        let layers = convex_layers(&points);
+        assert_eq!(layers.layers, vec![vec![0, 4, 1, 2, 3], vec![5, 6, 7, 10, 8], vec![9]]);
+        assert_eq!(layers.depth, vec![0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 1]);
+
+        // a collinear core
+        let layers = convex_layers(&[p(0., 0.), p(4., 0.), p(2., 4.), p(2., 2.), p(1.5, 1.), p(2.25, 2.5)]);
+        assert_eq!(layers.layers, vec![vec![0, 1, 2], vec![4, 3, 5]]);
+
+        assert!(convex_layers(&[]).layers.is_empty());
+    }
+
+    #[test]
+    fn layers_brute_force() {
+        let points = from_flat(&get_test_vector_gaussian(300));
+        let layers = convex_layers(&points);
+
+        // repeated peeling with andrew
+        let mut remaining: Vec<usize> = (0..points.len()).collect();
+        for layer in &layers.layers {
+            let hull: Vec<usize> = andrew_indices_with(&gather(&points, &remaining), Boundary::All).into_iter()
+                .map(|k| remaining[k])
+                .collect();
+            assert_eq!(layer, &hull);
+            remaining.retain(|i| !layer.contains(i));
+        }
+        assert!(remaining.is_empty());
+        assert!(layers.layers.len() > 10);
+    }
+
+    /// peels the points not strictly behind every facet of the hull of the
+    /// remaining points, including duplicates
+    fn peel_brute_force(points: &[Point3]) -> Vec<Vec<usize>> {
+        let mut remaining: Vec<usize> = (0..points.len()).collect();
+        let mut layers = Vec::new();
+        while !remaining.is_empty() {
+            let subset: Vec<Point3> = remaining.iter().map(|&i| points[i]).collect();
+            let layer: Vec<usize> = match try_quickhull3d(&subset) {
+                Ok(hull) => remaining.iter()
+                    .cloned()
+                    .filter(|&i| hull.iter().any(|f| orient3d(f.vertices[0], f.vertices[1], f.vertices[2], points[i]) == 0.))
+                    .collect(),
+                Err(_) => remaining.clone(),
+            };
+            remaining.retain(|i| !layer.contains(i));
+            layers.push(layer);
+        }
+        layers
+    }
+
+    #[test]
+    fn layers_lattice() {
+        let p = Point3::new;
+        let points = [
+            p(-2., -2., 0.), p(2., 2., 2.), p(2., 0., 2.), p(-2., 1., -1.), p(2., 0., -1.), p(2., -2., 1.),
+            p(1., 2., 1.), p(1., -1., -2.), p(-2., -2., 2.), p(-2., 0., -2.), p(2., -2., -1.), p(-2., -2., -2.),
+            p(-2., 1., 0.), p(0., -2., -2.), p(2., -2., -1.), p(-2., 2., 1.), p(2., 1., -1.),
+        ];
+        let layers = convex_layers_3d(&points);
+        assert_eq!(layers.layers.len(), 1);
+        assert_eq!(sorted(layers.layers[0].clone()), (0..points.len()).collect::<Vec<_>>());
+
+        for seed in 0..300 {
+            let points = get_random_lattice_3d(seed, 10 + seed as usize % 71, 2);
+            let layers: Vec<Vec<usize>> = convex_layers_3d(&points).layers.into_iter().map(sorted).collect();
+            assert_eq!(layers, peel_brute_force(&points), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn layers_cubes() {
+        let mut points = get_simple_cube();
+        let inner: Vec<Point3> = get_simple_cube().into_iter().map(|p| p * 0.5 + Point3::new(0.25, 0.25, 0.25)).collect();
+        points.extend(inner);
+        points.push(Point3::new(0.5, 0., 0.5));
+        points.push(Point3::new(0.5, 0.5, 0.5));
+        points.push(Point3::new(0., 0., 0.));
+
+        let layers = convex_layers_3d(&points);
+        assert_eq!(layers.layers, vec![vec![0, 18, 1, 2, 3, 4, 5, 6, 7, 16], (8..16).collect(), vec![17]]);
+        assert_eq!(layers.depth[16..], [0, 2, 0]);
+
+        // nested shells of a lattice cube, many points lie on the pruning polytope
+        let mut points = Vec::new();
+        for x in 0..5 {
+            for y in 0..5 {
+                for z in 0..5 {
+                    points.push(Point3::new(x as f64, y as f64, z as f64));
+                }
+            }
+        }
+        let sizes: Vec<usize> = convex_layers_3d(&points).layers.iter().map(|l| l.len()).collect();
+        assert_eq!(sizes, vec![98, 26, 1]);
+
+        let points = get_test_vector_gaussian_3d(200);
+        let layers: Vec<Vec<usize>> = convex_layers_3d(&points).layers.into_iter().map(sorted).collect();
+        assert_eq!(layers, peel_brute_force(&points));
+    }
+}
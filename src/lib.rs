@@ -1,29 +1,64 @@
 pub mod util;
+pub mod error;
+pub mod predicates;
+pub mod boundary;
 
 #[cfg(feature = "visual")]
 pub mod visualization;
 
 
 // 2d
+mod d2;
 mod primitives;
 pub mod andrew;
+pub mod graham;
 pub mod quickhull;
 pub mod jarvis;
 pub mod chan;
+pub mod kirkpatrick;
 pub mod akl;
+pub mod algorithm;
 
 // 3d
 mod d3;
 pub mod quickhull3d;
+pub mod mesh;
+pub mod stats;
+pub mod calipers;
+pub mod welzl;
+pub mod convex;
+pub mod intersection;
+pub mod minkowski;
+pub mod gjk;
+pub mod layers;
+pub mod incremental;
 
 // reexports:
-pub use akl::akl;
-pub use andrew::andrew;
-pub use jarvis::jarvis;
-pub use chan::chan;
-pub use quickhull::quickhull;
-pub use quickhull3d::quickhull3d;
-pub use primitives::area;
-pub use d3::{surface, Point3, Facet3};
+pub use akl::{akl, akl_points, akl_indices};
+pub use algorithm::{ConvexHull2d, Algorithm, Andrew, Graham, Jarvis, Chan, Kirkpatrick, Quickhull, Akl};
+pub use andrew::{andrew, andrew_points, andrew_indices, andrew_indices_with, try_andrew};
+pub use graham::{graham, graham_points, graham_indices, graham_indices_with, try_graham};
+pub use jarvis::{jarvis, jarvis_points, jarvis_indices, jarvis_indices_with, try_jarvis};
+pub use chan::{chan, chan_points, chan_indices, chan_indices_with, try_chan};
+pub use kirkpatrick::{kirkpatrick, kirkpatrick_points, kirkpatrick_indices, kirkpatrick_indices_with, try_kirkpatrick};
+pub use quickhull::{quickhull, quickhull_points, quickhull_indices, quickhull_indices_with, try_quickhull};
+#[cfg(feature = "parallel")]
+pub use quickhull::{par_quickhull, par_quickhull_points, par_quickhull_indices};
+pub use quickhull3d::{quickhull3d, quickhull3d_indices, quickhull3d_boundary, try_quickhull3d, try_quickhull3d_indices, quickhull3d_mesh, try_quickhull3d_mesh};
+pub use mesh::{HullMesh3, HalfEdge};
+pub use stats::HullStats2d;
+pub use calipers::{antipodal_pairs, farthest_pair, diameter, min_width, Width, min_area_rectangle, min_perimeter_rectangle, Rectangle};
+pub use welzl::{min_enclosing_circle, min_enclosing_circle_hull, min_enclosing_sphere, min_enclosing_sphere_hull, Circle, Sphere};
+pub use convex::{ConvexPolygon, ConvexPolytope};
+pub use intersection::{intersect_polygons, intersect_hulls};
+pub use minkowski::{minkowski_sum, minkowski_difference, minkowski_sum_3d, minkowski_difference_3d};
+pub use gjk::{gjk_intersects, gjk_intersects_3d, gjk_distance, gjk_distance_3d, epa_penetration, epa_penetration_3d, Support2d, Support3d, Distance, Penetration};
+pub use layers::{convex_layers, convex_layers_3d, ConvexLayers};
+pub use incremental::IncrementalHull2d;
+pub use primitives::{area, area_points, perimeter, perimeter_points, centroid_points, second_moments};
+pub use d2::{Point2, from_flat, try_from_flat, to_flat, gather};
+pub use error::HullError;
+pub use boundary::Boundary;
+pub use d3::{surface, mean_width, volume, centroid, inertia_tensor, Point3, Facet3};
 #[cfg(feature = "visual")] pub use d3::threejs;
 #[cfg(feature = "visual")] pub use visualization::svg;
@@ -4,7 +4,9 @@ fn main() {
     let v = get_test_vector_gaussian(300);
     convex_hulls::chan(&v);
     convex_hulls::jarvis(&v);
+    convex_hulls::kirkpatrick(&v);
     convex_hulls::andrew(&v);
+    convex_hulls::graham(&v);
     convex_hulls::quickhull(&v);
 
     let v = get_test_vector_gaussian_3d(300);
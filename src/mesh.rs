@@ -0,0 +1,418 @@
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+use crate::d3::{Point3, Facet3};
+use crate::predicates::orient3d;
+
+/// directed edge on the boundary of a face, counter-clockwise seen from outside
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HalfEdge {
+    /// vertex the half-edge starts at
+    pub origin: usize,
+    /// the oppositely directed half-edge of the neighbouring face
+    pub twin: usize,
+    /// the following half-edge on the boundary of the same face
+    pub next: usize,
+    pub face: usize,
+}
+
+/// surface of a 3D hull as a doubly connected edge list
+///
+/// all indices refer to the vectors of this structure, `indices` maps the
+/// vertices back to the input points
+#[derive(Debug, Clone, PartialEq)]
+pub struct HullMesh3 {
+    pub vertices: Vec<Point3>,
+    /// index of every vertex in the input pointset
+    pub indices: Vec<usize>,
+    pub half_edges: Vec<HalfEdge>,
+    /// one half-edge on the boundary of every face
+    pub faces: Vec<usize>,
+    /// one outgoing half-edge of every vertex
+    pub vertex_edges: Vec<usize>,
+}
+
+impl HullMesh3 {
+    /// builds the mesh from triangles given as indices into `pointset`, which
+    /// have to form a closed surface, counter-clockwise seen from outside
+    pub fn from_triangles(pointset: &[Point3], triangles: &[[usize; 3]]) -> HullMesh3 {
+        let polygons: Vec<&[usize]> = triangles.iter().map(|t| &t[..]).collect();
+        HullMesh3::from_polygons(pointset, &polygons)
+    }
+
+    /// builds the mesh from facets, equal vertices of different facets are
+    /// identified with each other
+    pub fn from_facets(facets: &[Facet3]) -> HullMesh3 {
+        let mut index = HashMap::new();
+        let mut pointset = Vec::new();
+        let triangles: Vec<[usize; 3]> = facets.iter()
+            .map(|f| f.vertices.map(|p| {
+                *index.entry([p.x.to_bits(), p.y.to_bits(), p.z.to_bits()]).or_insert_with(|| {
+                    pointset.push(p);
+                    pointset.len() - 1
+                })
+            }))
+            .collect();
+
+        HullMesh3::from_triangles(&pointset, &triangles)
+    }
+
+    /// builds the mesh from convex polygons given as indices into `pointset`,
+    /// which have to form a closed surface, counter-clockwise seen from outside
+    pub fn from_polygons<T: AsRef<[usize]>>(pointset: &[Point3], polygons: &[T]) -> HullMesh3 {
+        let mut compact = HashMap::new();
+        let mut indices = Vec::new();
+        for &i in polygons.iter().flat_map(|p| p.as_ref()) {
+            compact.entry(i).or_insert_with(|| {
+                indices.push(i);
+                indices.len() - 1
+            });
+        }
+
+        let vertices = indices.iter().map(|&i| pointset[i]).collect();
+        let mut half_edges = Vec::new();
+        let mut faces = Vec::with_capacity(polygons.len());
+        let mut vertex_edges = vec![0; indices.len()];
+        let mut directed = HashMap::new();
+
+        for (f, polygon) in polygons.iter().enumerate() {
+            let polygon = polygon.as_ref();
+            let n = polygon.len();
+            let first = half_edges.len();
+            faces.push(first);
+            for k in 0..n {
+                let (a, b) = (compact[&polygon[k]], compact[&polygon[(k+1) % n]]);
+                half_edges.push(HalfEdge {
+                    origin: a,
+                    twin: usize::MAX,
+                    next: first + (k+1) % n,
+                    face: f,
+                });
+                vertex_edges[a] = first + k;
+                directed.insert((a, b), first + k);
+            }
+        }
+
+        for e in 0..half_edges.len() {
+            let a = half_edges[e].origin;
+            let b = half_edges[half_edges[e].next].origin;
+            half_edges[e].twin = *directed.get(&(b, a)).expect("the polygons do not form a closed surface");
+        }
+
+        HullMesh3 {
+            vertices,
+            indices,
+            half_edges,
+            faces,
+            vertex_edges,
+        }
+    }
+
+    /// merges adjacent faces into convex polygons, if all their vertices are
+    /// at most `tolerance` away from a common plane, vertices which end up
+    /// inside of a face or on a straight edge are removed
+    ///
+    /// with a tolerance of zero only exactly coplanar faces are merged
+    pub fn merge_coplanar(&self, tolerance: f64) -> HullMesh3 {
+        // grow every group from a seed face, whose plane all members have to
+        // be close to, such that the tolerance does not accumulate
+        let mut group = vec![usize::MAX; self.num_faces()];
+        let mut groups = 0;
+        for seed in 0..self.num_faces() {
+            if group[seed] != usize::MAX {
+                continue
+            }
+            let plane: Vec<Point3> = self.face_vertices(seed).take(3).map(|v| self.vertices[v]).collect();
+            let normal = (plane[1] - plane[0]).cross(plane[2] - plane[0]).length();
+            let on_plane = |v: usize| {
+                let o = orient3d(plane[0], plane[1], plane[2], self.vertices[v]);
+                o == 0. || o.abs() <= tolerance * normal
+            };
+
+            group[seed] = groups;
+            let mut stack = vec![seed];
+            while let Some(f) = stack.pop() {
+                for g in self.face_neighbours(f) {
+                    if group[g] == usize::MAX && self.face_vertices(g).all(on_plane) {
+                        group[g] = groups;
+                        stack.push(g);
+                    }
+                }
+            }
+            groups += 1;
+        }
+
+        // half-edges between faces of the same group vanish
+        let he = &self.half_edges;
+        let keep = |e: usize| group[he[e].face] != group[he[he[e].twin].face];
+        let next = |e: usize| {
+            let mut c = he[e].next;
+            while !keep(c) {
+                c = he[he[c].twin].next;
+            }
+            c
+        };
+
+        let mut degree = vec![0; self.num_vertices()];
+        for e in (0..he.len()).filter(|&e| keep(e)) {
+            degree[he[e].origin] += 1;
+        }
+
+        let mut visited = vec![false; he.len()];
+        let mut polygons = Vec::with_capacity(groups);
+        for start in 0..he.len() {
+            if visited[start] || !keep(start) {
+                continue
+            }
+            let mut polygon = Vec::new();
+            let mut e = start;
+            while !visited[e] {
+                visited[e] = true;
+                // a vertex with two edges lies on the straight edge of the polytope
+                if degree[he[e].origin] > 2 {
+                    polygon.push(he[e].origin);
+                }
+                e = next(e);
+            }
+            polygons.push(polygon);
+        }
+
+        let mut mesh = HullMesh3::from_polygons(&self.vertices, &polygons);
+        for i in mesh.indices.iter_mut() {
+            *i = self.indices[*i];
+        }
+        mesh
+    }
+
+    pub fn num_vertices(&self) -> usize {
+        self.vertices.len()
+    }
+
+    pub fn num_edges(&self) -> usize {
+        self.half_edges.len() / 2
+    }
+
+    pub fn num_faces(&self) -> usize {
+        self.faces.len()
+    }
+
+    /// V - E + F, which is 2 for every closed surface of a convex body
+    pub fn euler_characteristic(&self) -> isize {
+        self.num_vertices() as isize - self.num_edges() as isize + self.num_faces() as isize
+    }
+
+    /// the vertex the half-edge `e` points to
+    pub fn target(&self, e: usize) -> usize {
+        self.half_edges[self.half_edges[e].next].origin
+    }
+
+    /// half-edges on the boundary of face `f` in counter-clockwise order
+    pub fn face_half_edges(&self, f: usize) -> impl Iterator<Item = usize> + '_ {
+        let start = self.faces[f];
+        let mut e = Some(start);
+        std::iter::from_fn(move || {
+            let current = e?;
+            let next = self.half_edges[current].next;
+            e = if next == start { None } else { Some(next) };
+            Some(current)
+        })
+    }
+
+    /// vertices of face `f` in counter-clockwise order seen from outside
+    pub fn face_vertices(&self, f: usize) -> impl Iterator<Item = usize> + '_ {
+        self.face_half_edges(f).map(move |e| self.half_edges[e].origin)
+    }
+
+    /// faces sharing an edge with face `f`
+    pub fn face_neighbours(&self, f: usize) -> impl Iterator<Item = usize> + '_ {
+        self.face_half_edges(f).map(move |e| self.half_edges[self.half_edges[e].twin].face)
+    }
+
+    /// half-edges starting at vertex `v`, going around it once
+    pub fn vertex_half_edges(&self, v: usize) -> impl Iterator<Item = usize> + '_ {
+        let start = self.vertex_edges[v];
+        let mut e = Some(start);
+        std::iter::from_fn(move || {
+            let current = e?;
+            // the twin points to v, so the edge after it starts at v again
+            let next = self.half_edges[self.half_edges[current].twin].next;
+            e = if next == start { None } else { Some(next) };
+            Some(current)
+        })
+    }
+
+    /// faces incident to vertex `v`
+    pub fn vertex_faces(&self, v: usize) -> impl Iterator<Item = usize> + '_ {
+        self.vertex_half_edges(v).map(move |e| self.half_edges[e].face)
+    }
+
+    /// outward unit normal of face `f`
+    pub fn face_normal(&self, f: usize) -> Point3 {
+        let v: Vec<Point3> = self.face_vertices(f).take(3).map(|i| self.vertices[i]).collect();
+        let n = (v[1] - v[0]).cross(v[2] - v[0]);
+        n / n.length()
+    }
+
+    /// index of the vertex farthest in `direction`
+    pub fn support(&self, direction: Point3) -> usize {
+        self.support_from(direction, 0)
+    }
+
+    /// like `support`, but the search starts at vertex `start`, which is
+    /// faster for slowly changing directions
+    ///
+    /// on a convex polytope a vertex without a higher neighbour is a global
+    /// maximum, so it suffices to climb along the edges
+    pub fn support_from(&self, direction: Point3, start: usize) -> usize {
+        let height = |v: usize| direction.dot(self.vertices[v]);
+        let mut current = start;
+        loop {
+            let higher = self.vertex_half_edges(current)
+                .map(|e| self.target(e))
+                .find(|&v| height(v) > height(current));
+            match higher {
+                Some(v) => current = v,
+                None => return current,
+            }
+        }
+    }
+
+    /// width averaged over all directions, i.e., the integral of the support
+    /// function over the unit sphere divided by 2 pi
+    ///
+    /// for a polytope this is the sum of the edge lengths times their
+    /// exterior dihedral angles divided by 4 pi
+    pub fn mean_width(&self) -> f64 {
+        let sum: f64 = (0..self.half_edges.len())
+            .filter(|&e| e < self.half_edges[e].twin)
+            .map(|e| {
+                let h = &self.half_edges[e];
+                let length = (self.vertices[self.target(e)] - self.vertices[h.origin]).length();
+                let n1 = self.face_normal(h.face);
+                let n2 = self.face_normal(self.half_edges[h.twin].face);
+                length * n1.cross(n2).length().atan2(n1.dot(n2))
+            })
+            .sum();
+        sum / (4. * PI)
+    }
+
+    /// the faces as triangles, polygons are split into fans
+    pub fn facets(&self) -> Vec<Facet3> {
+        (0..self.num_faces())
+            .flat_map(|f| {
+                let v: Vec<Point3> = self.face_vertices(f).map(|i| self.vertices[i]).collect();
+                (1..v.len()-1)
+                    .map(move |k| Facet3 { vertices: [v[0], v[k], v[k+1]] })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{quickhull3d, quickhull3d_mesh};
+    use crate::util::tests::{get_cube, get_simple_cube};
+
+    /// octahedron around the origin, whose vertices are the unit vectors
+    fn octahedron() -> (Vec<Point3>, Vec<[usize; 3]>) {
+        let points = vec![
+            Point3::new(1., 0., 0.),
+            Point3::new(-1., 0., 0.),
+            Point3::new(0., 1., 0.),
+            Point3::new(0., -1., 0.),
+            Point3::new(0., 0., 1.),
+            Point3::new(0., 0., -1.),
+            // an unused point
+            Point3::new(0., 0., 0.),
+        ];
+        let triangles = vec![
+            [0, 2, 4], [2, 1, 4], [1, 3, 4], [3, 0, 4],
+            [2, 0, 5], [1, 2, 5], [3, 1, 5], [0, 3, 5],
+        ];
+        (points, triangles)
+    }
+
+    #[test]
+    fn octahedron_mesh() {
+        let (points, triangles) = octahedron();
+        let mesh = HullMesh3::from_triangles(&points, &triangles);
+
+        assert_eq!(mesh.num_vertices(), 6);
+        assert_eq!(mesh.num_edges(), 12);
+        assert_eq!(mesh.num_faces(), 8);
+        assert_eq!(mesh.euler_characteristic(), 2);
+
+        for (e, h) in mesh.half_edges.iter().enumerate() {
+            assert_eq!(mesh.half_edges[h.twin].twin, e);
+            assert_eq!(mesh.half_edges[h.twin].origin, mesh.target(e));
+        }
+        for f in 0..mesh.num_faces() {
+            assert_eq!(mesh.face_vertices(f).count(), 3);
+            let mut neighbours: Vec<usize> = mesh.face_neighbours(f).collect();
+            neighbours.sort_unstable();
+            neighbours.dedup();
+            assert_eq!(neighbours.len(), 3);
+            assert!(!neighbours.contains(&f));
+        }
+        for v in 0..mesh.num_vertices() {
+            assert_eq!(mesh.vertex_faces(v).count(), 4);
+            assert!(mesh.vertex_half_edges(v).all(|e| mesh.half_edges[e].origin == v));
+        }
+        assert!(!mesh.indices.contains(&6));
+    }
+
+    #[test]
+    fn merge_cube() {
+        let v = get_cube();
+        let mesh = quickhull3d_mesh(&v).merge_coplanar(0.);
+
+        assert_eq!(mesh.num_faces(), 6);
+        assert_eq!(mesh.num_vertices(), 8);
+        assert_eq!(mesh.num_edges(), 12);
+        assert_eq!(mesh.euler_characteristic(), 2);
+        assert!((0..6).all(|f| mesh.face_vertices(f).count() == 4));
+        assert!((0..8).all(|v| mesh.vertex_faces(v).count() == 3));
+        assert_eq!(mesh.facets().len(), 12);
+
+        let mesh = HullMesh3::from_facets(&quickhull3d(&v)).merge_coplanar(0.);
+        assert_eq!(mesh.num_faces(), 6);
+    }
+
+    #[test]
+    fn merge_tolerance() {
+        let mut v = get_simple_cube();
+        v[7] = Point3::new(1. + 1e-9, 1., 1.);
+        // slightly outside of the middle of an edge
+        v.push(Point3::new(0.5, -1e-9, -1e-9));
+
+        let mesh = quickhull3d_mesh(&v);
+        assert!(mesh.merge_coplanar(0.).num_faces() > 6);
+
+        let merged = mesh.merge_coplanar(1e-6);
+        assert_eq!(merged.num_faces(), 6);
+        assert_eq!(merged.num_vertices(), 8);
+        assert_eq!(merged.num_edges(), 12);
+        assert!(!merged.indices.contains(&8));
+    }
+
+    #[test]
+    fn support_and_mean_width() {
+        let v = get_cube();
+        let mesh = quickhull3d_mesh(&v);
+        let corner = mesh.support(Point3::new(1., 2., 3.));
+        assert_eq!(mesh.vertices[corner], Point3::new(1., 1., 1.));
+        assert!((mesh.mean_width() - 1.5).abs() < 1e-12);
+        assert!((mesh.merge_coplanar(0.).mean_width() - 1.5).abs() < 1e-12);
+
+        let v = crate::util::get_test_vector_gaussian_3d(2000);
+        let mesh = quickhull3d_mesh(&v);
+        for k in 0..500 {
+            let (theta, phi) = (k as f64 * 0.037, k as f64 * 0.113);
+            let d = Point3::new(theta.sin() * phi.cos(), theta.sin() * phi.sin(), theta.cos());
+            let max = v.iter().map(|&q| d.dot(q)).fold(f64::NEG_INFINITY, f64::max);
+            assert_eq!(d.dot(mesh.vertices[mesh.support_from(d, k % mesh.num_vertices())]), max);
+        }
+    }
+}
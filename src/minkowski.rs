@@ -0,0 +1,254 @@
+use crate::d2::Point2;
+use crate::d3::{Point3, Facet3};
+use crate::quickhull3d::quickhull3d;
+use crate::mesh::HullMesh3;
+
+/// index of the lowest vertex, the leftmost one on ties
+fn lowest(polygon: &[Point2]) -> usize {
+    (0..polygon.len()).fold(0, |min, i| {
+        if (polygon[i].y, polygon[i].x) < (polygon[min].y, polygon[min].x) { i } else { min }
+    })
+}
+
+/// Minkowski sum of two convex polygons in counter-clockwise order, which
+/// consist of extreme points only, in O(n + m)
+///
+/// starting at the lowest vertices of both, the edges are merged by their
+/// angle, such that the result is again counter-clockwise and parallel
+/// edges are joined
+pub fn minkowski_sum(p: &[Point2], q: &[Point2]) -> Vec<Point2> {
+    let (n, m) = (p.len(), q.len());
+    if n == 0 || m == 0 {
+        return Vec::new()
+    }
+
+    let (p0, q0) = (lowest(p), lowest(q));
+    let pt = |i: usize| p[(p0 + i) % n];
+    let qt = |j: usize| q[(q0 + j) % m];
+
+    let mut sum = Vec::with_capacity(n + m);
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        sum.push(pt(i) + qt(j));
+
+        let cross = (pt(i + 1) - pt(i)).cross(qt(j + 1) - qt(j));
+        if j == m || i < n && cross > 0. {
+            i += 1;
+        } else if i == n || cross < 0. {
+            j += 1;
+        } else {
+            i += 1;
+            j += 1;
+        }
+    }
+
+    // a single point has no edges, which leaves a duplicate at the end
+    if sum.len() > 1 && sum[0] == sum[sum.len()-1] {
+        sum.pop();
+    }
+    sum
+}
+
+/// Minkowski difference `p - q = p + (-q)`, which is the configuration space
+/// obstacle of `p` for an object `q`, i.e., the set of all translations of
+/// `q` which intersect `p`, in O(n + m)
+pub fn minkowski_difference(p: &[Point2], q: &[Point2]) -> Vec<Point2> {
+    let negative: Vec<Point2> = q.iter().map(|&x| -x).collect();
+    minkowski_sum(p, &negative)
+}
+
+// Every vertex of the sum of two polytopes is the sum of a vertex of each,
+// whose normal cones overlap, and these pairs are found on the meshes. The
+// normal cone of a vertex is either contained in a cone of the other
+// polytope, whose vertex is found by hill-climbing in a direction inside of
+// it, or it is crossed by the normal arc of an edge of the other polytope.
+// Following this arc across the other mesh visits every pair of crossing
+// edges once, like an overlay of the two Gaussian maps.
+
+/// pairs of a vertex of `a` and a vertex of `b` in a direction inside of the
+/// normal cone of the former
+fn nested(a: &HullMesh3, b: &HullMesh3, normals: &[Point3], pairs: &mut Vec<(usize, usize)>) {
+    let mut j = 0;
+    for i in 0..a.num_vertices() {
+        let direction = a.vertex_faces(i).fold(Point3::new(0., 0., 0.), |d, f| d + normals[f]);
+        // the previous vertex is usually close, since neighbours are created together
+        j = b.support_from(direction, j);
+        pairs.push((i, j));
+    }
+}
+
+/// pairs of the end points of the edges of `a` and the vertices of `b`,
+/// whose normal cones are crossed by the normal arcs of these edges
+fn arcs(a: &HullMesh3, b: &HullMesh3, normals: &[Point3], pairs: &mut Vec<(usize, usize)>) {
+    let mut j = 0;
+    for e in (0..a.half_edges.len()).filter(|&e| e < a.half_edges[e].twin) {
+        let h = &a.half_edges[e];
+        let (n1, n2) = (normals[h.face], normals[a.half_edges[h.twin].face]);
+        let (u, w) = (h.origin, a.target(e));
+
+        // the arc consists of the directions n1 + t (n2 - n1) for 0 <= t <= 1
+        j = b.support_from(n1, j);
+        let mut t = 0.;
+        let mut visited = vec![j];
+        loop {
+            pairs.push((u, j));
+            pairs.push((w, j));
+
+            // the neighbour, which becomes higher first along the arc
+            let next = b.vertex_half_edges(j)
+                .map(|k| b.target(k))
+                .filter(|k| !visited.contains(k))
+                .filter_map(|k| {
+                    let d = b.vertices[k] - b.vertices[j];
+                    let (g0, g1) = (n1.dot(d), n2.dot(d));
+                    if g1 <= g0 {
+                        return None
+                    }
+                    let root = -g0 / (g1 - g0);
+                    (root <= 1.).then_some((k, root.max(t)))
+                })
+                .min_by(|x, y| x.1.total_cmp(&y.1));
+
+            match next {
+                Some((k, root)) => {
+                    j = k;
+                    t = root;
+                    visited.push(k);
+                },
+                None => break,
+            }
+        }
+    }
+}
+
+/// Minkowski sum of two 3D hulls as returned by `quickhull3d`
+///
+/// the pairs of vertices, whose sums are vertices of the result, are found
+/// by hill-climbing and by following the normal arcs of the edges of each
+/// hull across the other one, such that only O(h_a + h_b + K) candidates
+/// are passed to `quickhull3d`, where K is the number of crossing edges of
+/// both Gaussian maps
+pub fn minkowski_sum_3d(a: &[Facet3], b: &[Facet3]) -> Vec<Facet3> {
+    let (a, b) = (HullMesh3::from_facets(a).merge_coplanar(0.), HullMesh3::from_facets(b).merge_coplanar(0.));
+    let normals = |m: &HullMesh3| -> Vec<Point3> { (0..m.num_faces()).map(|f| m.face_normal(f)).collect() };
+    let (na, nb) = (normals(&a), normals(&b));
+
+    let mut pairs = Vec::new();
+    nested(&a, &b, &na, &mut pairs);
+    arcs(&a, &b, &na, &mut pairs);
+
+    let mut reversed = Vec::new();
+    nested(&b, &a, &nb, &mut reversed);
+    arcs(&b, &a, &nb, &mut reversed);
+    pairs.extend(reversed.into_iter().map(|(j, i)| (i, j)));
+
+    pairs.sort_unstable();
+    pairs.dedup();
+    let sums: Vec<Point3> = pairs.into_iter()
+        .map(|(i, j)| a.vertices[i] + b.vertices[j])
+        .collect();
+    quickhull3d(&sums)
+}
+
+/// Minkowski difference `a - b = a + (-b)` of two 3D hulls, which is the
+/// configuration space obstacle of `a` for an object `b`
+pub fn minkowski_difference_3d(a: &[Facet3], b: &[Facet3]) -> Vec<Facet3> {
+    // the point reflection turns the facets inside out, which the reversed
+    // order of their vertices undoes
+    let negative: Vec<Facet3> = b.iter()
+        .map(|f| {
+            let [u, v, w] = f.vertices;
+            Facet3 { vertices: [u * -1., w * -1., v * -1.] }
+        })
+        .collect();
+    minkowski_sum_3d(a, &negative)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+    use crate::d2::from_flat;
+    use crate::d3::volume;
+    use crate::primitives::area_points;
+    use crate::util::{get_test_vector_2d, get_test_vector_gaussian, get_test_vector_3d, get_test_vector_gaussian_3d, is_convex};
+    use crate::util::tests::get_simple_cube;
+    use crate::andrew_points;
+    use crate::convex::ConvexPolytope;
+
+    #[test]
+    fn sum_squares() {
+        let p = Point2::new;
+        let square = vec![p(0., 0.), p(1., 0.), p(1., 1.), p(0., 1.)];
+        let triangle = vec![p(1., 1.), p(0., 1.), p(0., 0.)];
+
+        // parallel edges are joined
+        assert_eq!(minkowski_sum(&square, &square), vec![p(0., 0.), p(2., 0.), p(2., 2.), p(0., 2.)]);
+        assert_eq!(minkowski_sum(&square, &triangle), vec![p(0., 0.), p(1., 0.), p(2., 1.), p(2., 2.), p(0., 2.)]);
+        assert_eq!(minkowski_difference(&square, &square), vec![p(-1., -1.), p(1., -1.), p(1., 1.), p(-1., 1.)]);
+
+        assert_eq!(minkowski_sum(&square, &[p(1., 2.)]), vec![p(1., 2.), p(2., 2.), p(2., 3.), p(1., 3.)]);
+        assert_eq!(minkowski_sum(&[p(1., 2.)], &[p(1., 2.)]), vec![p(2., 4.)]);
+        assert_eq!(minkowski_sum(&[p(0., 0.), p(1., 0.)], &[p(0., 0.), p(0., 1.)]), square);
+        assert!(minkowski_sum(&square, &[]).is_empty());
+    }
+
+    #[test]
+    fn sum_brute_force() {
+        let a = andrew_points(from_flat(&get_test_vector_gaussian(500)));
+        let b = andrew_points(from_flat(&get_test_vector_2d(500)));
+
+        let pairs = |b: &[Point2]| -> Vec<Point2> {
+            andrew_points(a.iter().flat_map(|&x| b.iter().map(move |&y| x + y)).collect::<Vec<_>>())
+        };
+        let negative: Vec<Point2> = b.iter().map(|&x| -x).collect();
+
+        for (sum, expected) in [(minkowski_sum(&a, &b), pairs(&b)), (minkowski_difference(&a, &b), pairs(&negative))] {
+            assert_eq!(sum.len(), expected.len());
+            assert_approx_eq!(area_points(&sum), area_points(&expected));
+        }
+    }
+
+    #[test]
+    fn sum_cubes() {
+        let cube = quickhull3d(&get_simple_cube());
+        assert_approx_eq!(volume(&minkowski_sum_3d(&cube, &cube)), 8.);
+        assert_approx_eq!(volume(&minkowski_difference_3d(&cube, &cube)), 8.);
+    }
+
+    #[test]
+    fn sum_brute_force_3d() {
+        let octahedron = quickhull3d(&[
+            Point3::new(1., 0., 0.), Point3::new(-1., 0., 0.), Point3::new(0., 1., 0.),
+            Point3::new(0., -1., 0.), Point3::new(0., 0., 1.), Point3::new(0., 0., -1.),
+        ]);
+        let cube = quickhull3d(&get_simple_cube());
+        let gaussian = quickhull3d(&get_test_vector_gaussian_3d(300));
+        let uniform = quickhull3d(&get_test_vector_3d(100));
+
+        for (a, b) in [(&cube, &octahedron), (&gaussian, &uniform), (&gaussian, &cube), (&octahedron, &uniform)] {
+            let sum = minkowski_sum_3d(a, b);
+            let (u, v) = (HullMesh3::from_facets(a).vertices, HullMesh3::from_facets(b).vertices);
+            let all: Vec<Point3> = u.iter().flat_map(|&x| v.iter().map(move |&y| x + y)).collect();
+            let expected = quickhull3d(&all);
+
+            assert_approx_eq!(volume(&sum), volume(&expected), 1e-9 * volume(&expected));
+            let polytope = ConvexPolytope::new(sum);
+            assert!(all.iter().all(|&p| polytope.signed_distance(p).0 <= 1e-9));
+        }
+
+        // the difference against the brute force hull of all differences
+        for (a, b) in [(&gaussian, &uniform), (&octahedron, &gaussian)] {
+            let difference = minkowski_difference_3d(a, b);
+            let (u, v) = (HullMesh3::from_facets(a).vertices, HullMesh3::from_facets(b).vertices);
+            let all: Vec<Point3> = u.iter().flat_map(|&x| v.iter().map(move |&y| x - y)).collect();
+            assert_approx_eq!(volume(&difference), volume(&quickhull3d(&all)), 1e-9 * volume(&difference));
+            assert!(is_convex(&difference, &all));
+        }
+
+        // squares from the faces of the cube, triangles from the faces of the
+        // octahedron and parallelograms from pairs of their edges
+        let sum = HullMesh3::from_facets(&minkowski_sum_3d(&cube, &octahedron)).merge_coplanar(0.);
+        assert_eq!((sum.num_vertices(), sum.num_faces()), (24, 6 + 8 + 12));
+    }
+}
@@ -0,0 +1,367 @@
+use std::cmp::Ordering;
+
+use crate::d2::Point2;
+use crate::d3::Point3;
+use crate::primitives::dist2;
+
+// Robust geometric predicates after J. R. Shewchuk, "Adaptive Precision
+// Floating-Point Arithmetic and Fast Robust Geometric Predicates",
+// Discrete & Computational Geometry 18, 305 (1997).
+//
+// Every predicate is first evaluated in plain floating-point arithmetic.
+// If the error bound of this evaluation can not guarantee the sign, the
+// determinant is evaluated exactly as a floating-point expansion, i.e., a
+// sum of non-overlapping doubles. The signs are therefore exact for all
+// finite inputs, as long as no intermediate product over- or underflows.
+
+/// relative rounding error of a single operation, 2^-53
+const EPSILON: f64 = f64::EPSILON / 2.;
+const CCW_ERRBOUND: f64 = (3. + 16. * EPSILON) * EPSILON;
+const O3D_ERRBOUND: f64 = (7. + 56. * EPSILON) * EPSILON;
+const DIST_ERRBOUND: f64 = 8. * EPSILON;
+
+/// `a + b = x + y` exactly
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let x = a + b;
+    let b_virtual = x - a;
+    let a_virtual = x - b_virtual;
+    let b_roundoff = b - b_virtual;
+    let a_roundoff = a - a_virtual;
+    (x, a_roundoff + b_roundoff)
+}
+
+/// `a * b = x + y` exactly
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+    let x = a * b;
+    (x, a.mul_add(b, -x))
+}
+
+/// adds `b` to the expansion `e` exactly (Grow-Expansion with zero elimination)
+fn grow_expansion(e: &mut Vec<f64>, b: f64) {
+    let mut q = b;
+    let mut k = 0;
+    for i in 0..e.len() {
+        let (sum, roundoff) = two_sum(q, e[i]);
+        q = sum;
+        if roundoff != 0. {
+            e[k] = roundoff;
+            k += 1;
+        }
+    }
+    e.truncate(k);
+    if q != 0. {
+        e.push(q);
+    }
+}
+
+/// approximation of the value of an expansion with the correct sign
+fn estimate(e: &[f64]) -> f64 {
+    e.iter().sum()
+}
+
+/// exact sum of the products `x * y` of all terms `[x, y]`
+fn sum_of_products2(terms: &[[f64; 2]]) -> Vec<f64> {
+    let mut e = Vec::new();
+    for &[x, y] in terms {
+        let (p, q) = two_product(x, y);
+        grow_expansion(&mut e, q);
+        grow_expansion(&mut e, p);
+    }
+    e
+}
+
+/// exact sum of the products `x * y * z` of all terms `[x, y, z]`
+fn sum_of_products3(terms: &[[f64; 3]]) -> Vec<f64> {
+    let mut e = Vec::new();
+    for &[x, y, z] in terms {
+        let (p, q) = two_product(x, y);
+        let (p1, p0) = two_product(p, z);
+        let (q1, q0) = two_product(q, z);
+        grow_expansion(&mut e, q0);
+        grow_expansion(&mut e, q1);
+        grow_expansion(&mut e, p0);
+        grow_expansion(&mut e, p1);
+    }
+    e
+}
+
+/// terms of `sign * orient2d(a, b, c)` in raw coordinates
+fn orient2d_terms(a: Point2, b: Point2, c: Point2, sign: f64, terms: &mut Vec<[f64; 2]>) {
+    terms.extend_from_slice(&[
+        [sign * b.x, c.y], [-sign * b.y, c.x],
+        [-sign * a.x, c.y], [sign * a.y, c.x],
+        [sign * a.x, b.y], [-sign * a.y, b.x],
+    ]);
+}
+
+/// terms of `sign * det(p, q, r)` in raw coordinates
+fn det3_terms(p: Point3, q: Point3, r: Point3, sign: f64, terms: &mut Vec<[f64; 3]>) {
+    terms.extend_from_slice(&[
+        [sign * p.x, q.y, r.z], [-sign * p.x, q.z, r.y],
+        [-sign * p.y, q.x, r.z], [sign * p.y, q.z, r.x],
+        [sign * p.z, q.x, r.y], [-sign * p.z, q.y, r.x],
+    ]);
+}
+
+/// terms of `sign * orient3d(a, b, c, d)` in raw coordinates
+fn orient3d_terms(a: Point3, b: Point3, c: Point3, d: Point3, sign: f64, terms: &mut Vec<[f64; 3]>) {
+    det3_terms(b, c, d, sign, terms);
+    det3_terms(a, c, d, -sign, terms);
+    det3_terms(a, b, d, sign, terms);
+    det3_terms(a, b, c, -sign, terms);
+}
+
+/// floating-point evaluation of orient2d and a bound on its absolute error
+fn orient2d_fast(a: Point2, b: Point2, c: Point2) -> (f64, f64) {
+    let detleft = (a.x - c.x) * (b.y - c.y);
+    let detright = (a.y - c.y) * (b.x - c.x);
+    (detleft - detright, CCW_ERRBOUND * (detleft.abs() + detright.abs()))
+}
+
+/// floating-point evaluation of orient3d and a bound on its absolute error
+fn orient3d_fast(a: Point3, b: Point3, c: Point3, d: Point3) -> (f64, f64) {
+    let u = b - a;
+    let v = c - a;
+    let w = d - a;
+
+    let uyvz = u.y * v.z;
+    let uzvy = u.z * v.y;
+    let uzvx = u.z * v.x;
+    let uxvz = u.x * v.z;
+    let uxvy = u.x * v.y;
+    let uyvx = u.y * v.x;
+
+    let det = w.x * (uyvz - uzvy) + w.y * (uzvx - uxvz) + w.z * (uxvy - uyvx);
+    let permanent = w.x.abs() * (uyvz.abs() + uzvy.abs())
+        + w.y.abs() * (uzvx.abs() + uxvz.abs())
+        + w.z.abs() * (uxvy.abs() + uyvx.abs());
+
+    (det, O3D_ERRBOUND * permanent)
+}
+
+/// decides the sign of `(va ± ea) - (vb ± eb)` if the error intervals allow it
+fn cmp_filtered((va, ea): (f64, f64), (vb, eb): (f64, f64)) -> Option<Ordering> {
+    let diff = va - vb;
+    // the factor 2 covers the rounding of the difference and of the bounds
+    if diff.abs() > 2. * (ea + eb) {
+        diff.partial_cmp(&0.)
+    } else {
+        None
+    }
+}
+
+/// twice the signed area of the triangle `a`, `b`, `c`
+/// positive if the points are in counter-clockwise order, negative if clockwise
+/// and zero if they are collinear
+///
+/// the sign is exact, the magnitude an approximation
+pub fn orient2d(a: Point2, b: Point2, c: Point2) -> f64 {
+    let (det, errbound) = orient2d_fast(a, b, c);
+    if det.abs() > errbound {
+        return det
+    }
+
+    let mut terms = Vec::with_capacity(6);
+    orient2d_terms(a, b, c, 1., &mut terms);
+    estimate(&sum_of_products2(&terms))
+}
+
+/// six times the signed volume of the tetrahedron `a`, `b`, `c`, `d`
+/// positive if `d` lies on the side of the plane through `a`, `b`, `c` to which
+/// `(b - a) x (c - a)` points, i.e., if `a`, `b`, `c` appear counter-clockwise
+/// seen from `d`, zero if the points are coplanar
+///
+/// the sign is exact, the magnitude an approximation
+pub fn orient3d(a: Point3, b: Point3, c: Point3, d: Point3) -> f64 {
+    let (det, errbound) = orient3d_fast(a, b, c, d);
+    if det.abs() > errbound {
+        return det
+    }
+
+    let mut terms = Vec::with_capacity(24);
+    orient3d_terms(a, b, c, d, 1., &mut terms);
+    estimate(&sum_of_products3(&terms))
+}
+
+/// whether three points in space are on a line, evaluated exactly
+pub fn collinear3d(a: Point3, b: Point3, c: Point3) -> bool {
+    // if the points span a plane, its normal has a non-zero component,
+    // so at least one projection on the coordinate planes is a proper triangle
+    let xy = |p: Point3| Point2::new(p.x, p.y);
+    let yz = |p: Point3| Point2::new(p.y, p.z);
+    let zx = |p: Point3| Point2::new(p.z, p.x);
+
+    orient2d(xy(a), xy(b), xy(c)) == 0.
+        && orient2d(yz(a), yz(b), yz(c)) == 0.
+        && orient2d(zx(a), zx(b), zx(c)) == 0.
+}
+
+/// exact comparison of `orient2d(a, b, p)` and `orient2d(a, b, q)`, i.e.,
+/// which of `p` and `q` lies further left of the line from `a` to `b`
+pub fn cmp_orient2d(a: Point2, b: Point2, p: Point2, q: Point2) -> Ordering {
+    if let Some(ord) = cmp_filtered(orient2d_fast(a, b, p), orient2d_fast(a, b, q)) {
+        return ord
+    }
+
+    let mut terms = Vec::with_capacity(12);
+    orient2d_terms(a, b, p, 1., &mut terms);
+    orient2d_terms(a, b, q, -1., &mut terms);
+    estimate(&sum_of_products2(&terms)).partial_cmp(&0.).unwrap()
+}
+
+/// exact comparison of `orient3d(a, b, c, p)` and `orient3d(a, b, c, q)`, i.e.,
+/// which of `p` and `q` lies further in front of the plane through `a`, `b`, `c`
+pub fn cmp_orient3d(a: Point3, b: Point3, c: Point3, p: Point3, q: Point3) -> Ordering {
+    if let Some(ord) = cmp_filtered(orient3d_fast(a, b, c, p), orient3d_fast(a, b, c, q)) {
+        return ord
+    }
+
+    let mut terms = Vec::with_capacity(48);
+    orient3d_terms(a, b, c, p, 1., &mut terms);
+    orient3d_terms(a, b, c, q, -1., &mut terms);
+    estimate(&sum_of_products3(&terms)).partial_cmp(&0.).unwrap()
+}
+
+/// exact comparison of the slopes of the segments from `p` to `q` and from
+/// `r` to `s`, which both have to point to the right
+pub fn cmp_slopes(p: Point2, q: Point2, r: Point2, s: Point2) -> Ordering {
+    // slope(p, q) > slope(r, s) <=> (q - p) x (s - r) < 0
+    let detleft = (q.x - p.x) * (s.y - r.y);
+    let detright = (q.y - p.y) * (s.x - r.x);
+    let det = detleft - detright;
+    if det.abs() > CCW_ERRBOUND * (detleft.abs() + detright.abs()) {
+        return 0f64.partial_cmp(&det).unwrap()
+    }
+
+    let terms = [
+        [q.x, s.y], [-q.x, r.y], [-p.x, s.y], [p.x, r.y],
+        [-q.y, s.x], [q.y, r.x], [p.y, s.x], [-p.y, r.x],
+    ];
+    0f64.partial_cmp(&estimate(&sum_of_products2(&terms))).unwrap()
+}
+
+/// exact comparison of the distances of `p` and `q` from `a`
+pub fn cmp_dist2(a: Point2, p: Point2, q: Point2) -> Ordering {
+    let dp = dist2(a, p);
+    let dq = dist2(a, q);
+    if let Some(ord) = cmp_filtered((dp, DIST_ERRBOUND * dp), (dq, DIST_ERRBOUND * dq)) {
+        return ord
+    }
+
+    // |p-a|^2 - |q-a|^2 = p^2 - q^2 - 2a(p - q)
+    let terms = [
+        [p.x, p.x], [p.y, p.y], [-q.x, q.x], [-q.y, q.y],
+        [-2. * a.x, p.x], [-2. * a.y, p.y], [2. * a.x, q.x], [2. * a.y, q.y],
+    ];
+    estimate(&sum_of_products2(&terms)).partial_cmp(&0.).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{Rng, SeedableRng};
+    use rand_pcg::Pcg64;
+
+    /// exact orient2d for integer coordinates
+    fn orient2d_i128(a: (i64, i64), b: (i64, i64), c: (i64, i64)) -> i128 {
+        let (ax, ay, bx, by, cx, cy) = (a.0 as i128, a.1 as i128, b.0 as i128, b.1 as i128, c.0 as i128, c.1 as i128);
+        (bx - ax) * (cy - ay) - (by - ay) * (cx - ax)
+    }
+
+    #[test]
+    fn orient2d_near_collinear() {
+        // coordinates up to 2^40, such that the products are not representable
+        let mut rng: Pcg64 = SeedableRng::seed_from_u64(42);
+        let big = 1i64 << 40;
+        for _ in 0..10000 {
+            let a = (rng.gen_range(-big..big), rng.gen_range(-big..big));
+            let b = (rng.gen_range(-big..big), rng.gen_range(-big..big));
+            // a point close to the line through a and b
+            let t = rng.gen_range(-3..4);
+            let c = (a.0 + t * (b.0 - a.0) + rng.gen_range(-1..2), a.1 + t * (b.1 - a.1) + rng.gen_range(-1..2));
+            if c.0.abs() > 1 << 52 || c.1.abs() > 1 << 52 {
+                continue
+            }
+
+            let p = |x: (i64, i64)| Point2::new(x.0 as f64, x.1 as f64);
+            let exact = orient2d_i128(a, b, c);
+            let robust = orient2d(p(a), p(b), p(c));
+            assert_eq!(robust.partial_cmp(&0.), exact.partial_cmp(&0), "{:?} {:?} {:?}", a, b, c);
+        }
+    }
+
+    #[test]
+    fn orient2d_tiny_perturbation() {
+        // the classic example: points on a grid of ulps around the line y = x
+        let b = Point2::new(12., 12.);
+        let c = Point2::new(24., 24.);
+        for i in 0..64 {
+            for j in 0..64 {
+                let ulp = f64::EPSILON / 2.;
+                let a = Point2::new(0.5 + i as f64 * ulp, 0.5 + j as f64 * ulp);
+                // orient2d(a, b, c) = 12 (a.y - a.x)
+                assert_eq!(orient2d(a, b, c).partial_cmp(&0.), Some(j.cmp(&i)));
+            }
+        }
+    }
+
+    #[test]
+    fn orient3d_near_coplanar() {
+        let mut rng: Pcg64 = SeedableRng::seed_from_u64(42);
+        let big = 1i64 << 24;
+        let p = |x: [i64; 3]| Point3::new(x[0] as f64, x[1] as f64, x[2] as f64);
+        for _ in 0..10000 {
+            let mut r = || [rng.gen_range(-big..big), rng.gen_range(-big..big), rng.gen_range(-big..big)];
+            let (a, b, c) = (r(), r(), r());
+            // d = a + s(b - a) + t(c - a) + small offset
+            let (s, t) = (rng.gen_range(-2..3), rng.gen_range(-2..3));
+            let d: Vec<i64> = (0..3).map(|k| a[k] + s * (b[k] - a[k]) + t * (c[k] - a[k]) + rng.gen_range(-1..2)).collect();
+            let d = [d[0], d[1], d[2]];
+
+            let u: Vec<i128> = (0..3).map(|k| (b[k] - a[k]) as i128).collect();
+            let v: Vec<i128> = (0..3).map(|k| (c[k] - a[k]) as i128).collect();
+            let w: Vec<i128> = (0..3).map(|k| (d[k] - a[k]) as i128).collect();
+            let exact = w[0] * (u[1] * v[2] - u[2] * v[1])
+                + w[1] * (u[2] * v[0] - u[0] * v[2])
+                + w[2] * (u[0] * v[1] - u[1] * v[0]);
+
+            let robust = orient3d(p(a), p(b), p(c), p(d));
+            assert_eq!(robust.partial_cmp(&0.), exact.partial_cmp(&0));
+        }
+    }
+
+    #[test]
+    fn comparisons() {
+        let a = Point2::new(0.1, 0.1);
+        let b = Point2::new(0.7, 0.3);
+        let p = Point2::new(0.2, 0.9);
+
+        assert_eq!(cmp_orient2d(a, b, p, p), Ordering::Equal);
+        assert_eq!(cmp_orient2d(a, b, p, a), Ordering::Greater);
+        assert_eq!(cmp_dist2(a, p, p), Ordering::Equal);
+        assert_eq!(cmp_dist2(a, b, p), Ordering::Less);
+
+        // parallel segments far apart, and a slightly steeper one
+        let o = Point2::new(0., 0.);
+        let d = Point2::new(3., 2f64.powi(-50));
+        let e = Point2::new(1., 1.);
+        assert_eq!(cmp_slopes(o, d, e + d, e + d + d), Ordering::Equal);
+        assert_eq!(cmp_slopes(o, d, o, d + Point2::new(0., 1e-30)), Ordering::Less);
+        assert_eq!(cmp_slopes(b, p, a, b), Ordering::Greater);
+
+        // equal distance along different axes
+        let d = 3e-17;
+        assert_eq!(cmp_dist2(a, Point2::new(0.1 + d, 0.1), Point2::new(0.1, 0.1 + d)), Ordering::Equal);
+
+        let o = Point3::new(0., 0., 0.);
+        let x = Point3::new(1e-9, 0., 0.);
+        let y = Point3::new(0., 1e-9, 0.);
+        let above = Point3::new(0.3, 0.3, 1e-20);
+        assert!(orient3d(o, x, y, above) > 0.);
+        assert_eq!(cmp_orient3d(o, x, y, above, Point3::new(5., 5., 1e-20)), Ordering::Equal);
+
+        assert!(collinear3d(o, Point3::new(1., 2., 3.), Point3::new(3., 6., 9.)));
+        assert!(!collinear3d(o, Point3::new(1., 2., 3.), Point3::new(3., 6., 9. + 1e-15)));
+    }
+}
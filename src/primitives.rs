@@ -1,67 +1,165 @@
-use itertools::Itertools;
+use std::cmp::Ordering::Less;
 
-pub fn side(p1: (f64, f64), p2: (f64, f64), p: (f64, f64)) -> f64 {
-    (p2.1 - p1.1)*(p.0 - p1.0) + (-p2.0 + p1.0)*(p.1 - p1.1)
-}
+use crate::d2::{Point2, expect_flat};
+use crate::predicates::{orient2d, cmp_dist2};
 
-pub fn point_in_octagon(octagon: [(f64, f64); 8], p: (f64, f64)) -> bool {
+// the octagon is in clockwise order, i.e., inside points are right of every edge
+pub fn point_in_octagon(octagon: [Point2; 8], p: Point2) -> bool {
     // compare opposite sites first
-       side(octagon[0], octagon[1], p) > 0f64
-    && side(octagon[2], octagon[3], p) > 0f64
-    && side(octagon[4], octagon[5], p) > 0f64
-    && side(octagon[6], octagon[7], p) > 0f64
-    && side(octagon[1], octagon[2], p) > 0f64
-    && side(octagon[3], octagon[4], p) > 0f64
-    && side(octagon[5], octagon[6], p) > 0f64
-    && side(octagon[7], octagon[0], p) > 0f64
+       orient2d(octagon[0], octagon[1], p) < 0f64
+    && orient2d(octagon[2], octagon[3], p) < 0f64
+    && orient2d(octagon[4], octagon[5], p) < 0f64
+    && orient2d(octagon[6], octagon[7], p) < 0f64
+    && orient2d(octagon[1], octagon[2], p) < 0f64
+    && orient2d(octagon[3], octagon[4], p) < 0f64
+    && orient2d(octagon[5], octagon[6], p) < 0f64
+    && orient2d(octagon[7], octagon[0], p) < 0f64
+}
+
+pub fn dist2(a: Point2, b: Point2) -> f64 {
+    (a.x - b.x)*(a.x - b.x) + (a.y - b.y)*(a.y - b.y)
 }
 
-pub fn dist2(a: (f64, f64), b: (f64, f64)) -> f64 {
-    (a.0 - b.0)*(a.0 - b.0) + (a.1 - b.1)*(a.1 - b.1)
+pub fn cross2d(o: Point2, a: Point2, b: Point2) -> f64 {
+    (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
 }
 
-pub fn cross2d(o: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
-    (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+/// whether all points of the sequence are on the line through its first and last point
+pub fn all_collinear(pointset: &[Point2], sequence: &[usize]) -> bool {
+    let (first, last) = (pointset[sequence[0]], pointset[sequence[sequence.len()-1]]);
+    sequence.iter().all(|&i| orient2d(first, last, pointset[i]) == 0f64)
 }
 
+// coord stores a contiguous array of 2N floats in the format x1, y1, x2, y2, ...
+// an odd number of values panics
 pub fn area(coord: &[f64]) -> f64 {
-    coord.iter()
-         .chain(coord.iter().take(2)) // append the first point, to close the loop
-         .tuples::<(_, _)>()
-         .tuple_windows::<(_, _)>()
-         .fold(0f64, |sum, ((x1, y1), (x2, y2))| sum + (y1+y2) * (x1-x2)) / 2.
+    area_points(&expect_flat(coord, "area"))
 }
 
-fn cw(o: (f64, f64), a: (f64, f64), b: (f64, f64)) -> bool {
-    cross2d(o, a, b) > 0.
+/// signed area of a polygon, positive for counter-clockwise order
+pub fn area_points(polygon: &[Point2]) -> f64 {
+    // triangle fan around the first vertex, which avoids cancellation for
+    // polygons far away from the origin
+    match polygon.first() {
+        Some(&o) => polygon.windows(2)
+            .fold(0f64, |sum, w| sum + cross2d(o, w[0], w[1])) / 2.,
+        None => 0.,
+    }
 }
-fn ccw(o: (f64, f64), a: (f64, f64), b: (f64, f64)) -> bool {
-    cross2d(o, a, b) < 0.
+
+// coord stores a contiguous array of 2N floats in the format x1, y1, x2, y2, ...
+// an odd number of values panics
+pub fn perimeter(coord: &[f64]) -> f64 {
+    perimeter_points(&expect_flat(coord, "perimeter"))
+}
+
+/// length of the closed boundary of a polygon
+pub fn perimeter_points(polygon: &[Point2]) -> f64 {
+    match polygon.last() {
+        Some(&last) => polygon.iter()
+            .fold((0f64, last), |(sum, a), &b| (sum + (b - a).length(), b))
+            .0,
+        None => 0.,
+    }
+}
+
+/// signed area, centroid relative to `o` and second moments
+/// `[∫ x^2, ∫ y^2, ∫ x y] dA` relative to `o` of a polygon
+pub(crate) fn moments(polygon: &[Point2], o: Point2) -> (f64, Point2, [f64; 3]) {
+    let mut area = 0.;
+    let mut first = Point2::new(0., 0.);
+    let mut second = [0.; 3];
+
+    for (a, b) in polygon.iter().zip(polygon.iter().cycle().skip(1)) {
+        let (a, b) = (*a - o, *b - o);
+        let det = a.cross(b);
+        let s = a + b;
+
+        area += det / 2.;
+        first += s * (det / 6.);
+        second[0] += (a.x*a.x + b.x*b.x + s.x*s.x) * det / 24.;
+        second[1] += (a.y*a.y + b.y*b.y + s.y*s.y) * det / 24.;
+        second[2] += (a.x*a.y + b.x*b.y + s.x*s.y) * det / 24.;
+    }
+
+    let centroid = if area != 0. { first / area } else { first };
+    (area, centroid, second)
+}
+
+/// centroid of the area of a polygon
+pub fn centroid_points(polygon: &[Point2]) -> Point2 {
+    match polygon.first() {
+        Some(&o) => moments(polygon, o).1 + o,
+        None => Point2::new(0., 0.),
+    }
+}
+
+/// second moments of area `[∫ x^2, ∫ y^2, ∫ x y] dA` of a counter-clockwise
+/// polygon with respect to its centroid
+pub fn second_moments(polygon: &[Point2]) -> [f64; 3] {
+    let o = match polygon.first() {
+        Some(&o) => o,
+        None => return [0.; 3],
+    };
+    let (area, c, second) = moments(polygon, o);
+
+    // parallel axis theorem
+    [
+        second[0] - area * c.x * c.x,
+        second[1] - area * c.y * c.y,
+        second[2] - area * c.x * c.y,
+    ]
+}
+
+fn cw(o: Point2, a: Point2, b: Point2) -> bool {
+    orient2d(o, a, b) > 0.
+}
+fn ccw(o: Point2, a: Point2, b: Point2) -> bool {
+    orient2d(o, a, b) < 0.
+}
+
+/// the most clockwise point of `poly` seen from `p`, in O(N)
+/// on ties the point furthest away is taken, like in the Jarvis march
+fn tangent_linear(p: Point2, poly: &[usize], points: &[Point2]) -> usize {
+    poly.iter()
+        .cloned()
+        .filter(|&i| points[i] != p)
+        .reduce(|t, i| {
+            let orientation = orient2d(p, points[i], points[t]);
+            if orientation > 0. || orientation == 0. && cmp_dist2(p, points[t], points[i]) == Less {
+                i
+            } else {
+                t
+            }
+        })
+        .unwrap_or(poly[0])
 }
 
 // cmp: https://github.com/felipesfaria/ch_chan/blob/master/ch_chan/ch_chan.cpp
-pub fn tangent(p: (f64, f64), poly: &mut Vec<(f64, f64)>) -> (f64, f64) {
+// `poly` holds indices into `points` and returns the index of the tangent point
+pub fn tangent(p: Point2, poly: &mut [usize], points: &[Point2]) -> usize {
     // search for the tangent through `p` of the polygon `poly`
     // use a clever binary search
     // all points q before the tangent t are ptq oriented ccw and after cw
 
-    // special case of single points
-    if poly.len() == 2 {
-        return poly[0];
+    // special case of single points and segments
+    if poly.len() <= 3 {
+        return tangent_linear(p, poly, points);
     }
 
     // we have a problem if p is the first point of the hull for some reason
     // in that case I just reorder in O(N), which hopefully happens seldom
     // enough to not influence the runtime too bad
-    if poly[0] == p {
+    if points[poly[0]] == p {
         let n = poly.len();
         let first = poly[1];
-        for i in 0..(n-1) {
-            poly[i] = poly[i+1];
-        }
+        poly.copy_within(1.., 0);
         poly[n-1] = first;
     }
 
+    let poly = &*poly;
+    let pt = |i: usize| points[poly[i]];
+
     let n = poly.len()-1;
     let mut a = 0; // lower
     let mut b = n; // upper
@@ -71,31 +169,37 @@ pub fn tangent(p: (f64, f64), poly: &mut Vec<(f64, f64)>) -> (f64, f64) {
 
     // rightmost tangent = maximum for the isLeft() ordering
     // test if poly[0] is a local maximum
-    if ccw(p, poly[1], poly[0]) && !cw(p, poly[n-1], poly[0]) {
+    if ccw(p, pt(1), pt(0)) && !cw(p, pt(n-1), pt(0)) {
         return poly[0];
     }
 
     loop {
         c = (a + b) / 2;
 
+        // the search interval can not shrink any further, which only happens
+        // for degenerate polygons, e.g., with collinear vertices
+        if c == a {
+            return tangent_linear(p, poly, points);
+        }
+
         // if we encounter our selves, the next point is a tangent (due to convexivity)
-        if poly[c] == p {
+        if pt(c) == p {
             return poly[c+1];
         }
 
-        cw_c = ccw(p, poly[c+1], poly[c]);
+        cw_c = ccw(p, pt(c+1), pt(c));
         // is c the tangent?
-        if cw_c && !cw(p, poly[c-1], poly[c]) {
+        if cw_c && !cw(p, pt(c-1), pt(c)) {
             return poly[c];
         }
 
         // continue with the binary search
-        ccw_a = cw(p, poly[a+1], poly[a]);
+        ccw_a = cw(p, pt(a+1), pt(a));
         if ccw_a {
             if cw_c {
                 b = c;
             } else {
-                if cw(p, poly[a], poly[c]) {
+                if cw(p, pt(a), pt(c)) {
                     b = c;
                 } else {
                     a = c;
@@ -106,7 +210,7 @@ pub fn tangent(p: (f64, f64), poly: &mut Vec<(f64, f64)>) -> (f64, f64) {
             if !cw_c {
                 a = c;
             } else {
-                if ccw(p, poly[a], poly[c]) {
+                if ccw(p, pt(a), pt(c)) {
                     b = c;
                 } else {
                     a = c;
@@ -115,3 +219,43 @@ pub fn tangent(p: (f64, f64), poly: &mut Vec<(f64, f64)>) -> (f64, f64) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+    use crate::andrew_points;
+    use crate::d2::from_flat;
+    use crate::util::tests::get_square;
+
+    #[test]
+    fn square_moments() {
+        let hull = andrew_points(from_flat(&get_square()));
+
+        assert_approx_eq!(perimeter_points(&hull), 4.);
+        assert_approx_eq!(perimeter(&get_square()[..6]), 2. + 2f64.sqrt());
+        let c = centroid_points(&hull);
+        assert_approx_eq!(c.x, 0.5);
+        assert_approx_eq!(c.y, 0.5);
+
+        let [xx, yy, xy] = second_moments(&hull);
+        assert_approx_eq!(xx, 1. / 12.);
+        assert_approx_eq!(yy, 1. / 12.);
+        assert_approx_eq!(xy, 0.);
+    }
+
+    #[test]
+    fn triangle_moments() {
+        let p = Point2::new;
+        let triangle = [p(3., 3.), p(5., 3.), p(3., 4.)];
+
+        let c = centroid_points(&triangle);
+        assert_approx_eq!(c.x, 3. + 2. / 3.);
+        assert_approx_eq!(c.y, 3. + 1. / 3.);
+        // b h^3 / 36 and b^3 h / 36 for a right triangle
+        let [xx, yy, xy] = second_moments(&triangle);
+        assert_approx_eq!(xx, 8. / 36.);
+        assert_approx_eq!(yy, 2. / 36.);
+        assert_approx_eq!(xy, -4. / 72.);
+    }
+}
@@ -1,155 +1,305 @@
-use itertools::Itertools;
+use std::cmp::Ordering::Less;
 
-use crate::primitives::cross2d;
+use crate::d2::{Point2, expect_flat, try_from_flat, to_flat, gather};
+use crate::error::{HullError, validate_2d};
+use crate::boundary::{Boundary, insert_boundary_points};
+use crate::predicates::{orient2d, cmp_orient2d};
 
-// TODO: rayon parallel version
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
-#[cfg(not(feature = "visual"))]
+// points stores a contiguous array of 2N floats in the format x1, y1, x2, y2, ...
+// an odd number of values panics
 pub fn quickhull(pointset: &[f64]) -> Vec<f64> {
-    if pointset.len() < 3*2 {
-        return pointset.to_vec()
+    to_flat(&quickhull_points(expect_flat(pointset, "quickhull")))
+}
+
+// points stores a contiguous array of 2N floats in the format x1, y1, x2, y2, ...
+pub fn try_quickhull(pointset: &[f64]) -> Result<Vec<f64>, HullError> {
+    let pointset = try_from_flat(pointset)?;
+    validate_2d(&pointset)?;
+    Ok(to_flat(&gather(&pointset, &hull(&pointset))))
+}
+
+pub fn quickhull_points<P: Into<Point2>>(pointset: impl IntoIterator<Item = P>) -> Vec<Point2> {
+    let pointset: Vec<Point2> = pointset.into_iter().map(Into::into).collect();
+    gather(&pointset, &hull(&pointset))
+}
+
+/// indices of the hull vertices in counter-clockwise order
+pub fn quickhull_indices(pointset: &[Point2]) -> Vec<usize> {
+    hull(pointset)
+}
+
+/// like `quickhull_indices`, but with `Boundary::All` the points on the edges are kept
+pub fn quickhull_indices_with(pointset: &[Point2], boundary: Boundary) -> Vec<usize> {
+    match boundary {
+        Boundary::Extreme => hull(pointset),
+        Boundary::All => insert_boundary_points(pointset, hull(pointset)),
     }
+}
+
+/// below this number of candidates the parallel version continues sequentially
+#[cfg(feature = "parallel")]
+const PARALLEL_THRESHOLD: usize = 1 << 14;
+
+// points stores a contiguous array of 2N floats in the format x1, y1, x2, y2, ...
+// an odd number of values panics
+#[cfg(feature = "parallel")]
+pub fn par_quickhull(pointset: &[f64]) -> Vec<f64> {
+    to_flat(&par_quickhull_points(expect_flat(pointset, "par_quickhull")))
+}
+
+#[cfg(feature = "parallel")]
+pub fn par_quickhull_points<P: Into<Point2>>(pointset: impl IntoIterator<Item = P>) -> Vec<Point2> {
+    let pointset: Vec<Point2> = pointset.into_iter().map(Into::into).collect();
+    gather(&pointset, &par_hull(&pointset))
+}
+
+/// like `quickhull_indices`, but the recursion runs on all threads of the rayon
+/// thread pool, the output is identical to the sequential version
+#[cfg(feature = "parallel")]
+pub fn par_quickhull_indices(pointset: &[Point2]) -> Vec<usize> {
+    par_hull(pointset)
+}
+
+/// indices of the lexicographically smallest and largest points, which are both corners
+fn extrema(pointset: &[Point2]) -> (usize, usize) {
+    (0..pointset.len())
+        .fold((0, 0), |(min, max), i| {
+            let (p, pmin, pmax) = (pointset[i], pointset[min], pointset[max]);
+            let min = if (p.x, p.y) < (pmin.x, pmin.y) { i } else { min };
+            let max = if (p.x, p.y) > (pmax.x, pmax.y) { i } else { max };
+            (min, max)
+        })
+}
+
+/// all candidates strictly on the outer side of the directed edge from `a` to `b`
+fn outside(pointset: &[Point2], candidates: &[usize], a: usize, b: usize) -> Vec<usize> {
+    let (a, b) = (pointset[a], pointset[b]);
+    candidates.iter()
+        .cloned()
+        .filter(|&i| orient2d(a, pointset[i], b) > 0f64)
+        .collect()
+}
+
+fn farthest(pointset: &[Point2], candidates: &[usize], a: usize, b: usize) -> usize {
+    let (pa, pb) = (pointset[a], pointset[b]);
+    candidates.iter()
+        .cloned()
+        .fold(b, |farthest, i| if cmp_orient2d(pa, pb, pointset[farthest], pointset[i]) == Less {farthest} else {i})
+}
 
-    let start = (pointset[0], pointset[1]);
-    let (min, max) = pointset.iter()
-        .tuples::<(_, _)>()
-        .fold((start, start), |(min, max), (&x, &y)| {
-            let min = if x < min.0 { (x, y) } else { min };
-            let max = if x > max.0 { (x, y) } else { max };
+#[cfg(feature = "parallel")]
+fn par_extrema(pointset: &[Point2]) -> (usize, usize) {
+    // on ties the first index wins, like in the sequential fold
+    (0..pointset.len())
+        .into_par_iter()
+        .map(|i| (i, i))
+        .reduce(|| (0, 0), |(min1, max1), (min2, max2)| {
+            let (pmin1, pmax1, pmin2, pmax2) = (pointset[min1], pointset[max1], pointset[min2], pointset[max2]);
+            let min = if (pmin2.x, pmin2.y) < (pmin1.x, pmin1.y) { min2 } else { min1 };
+            let max = if (pmax2.x, pmax2.y) > (pmax1.x, pmax1.y) { max2 } else { max1 };
             (min, max)
-        });
+        })
+}
+
+#[cfg(feature = "parallel")]
+fn par_outside(pointset: &[Point2], candidates: &[usize], a: usize, b: usize) -> Vec<usize> {
+    let (a, b) = (pointset[a], pointset[b]);
+    candidates.par_iter()
+        .cloned()
+        .filter(|&i| orient2d(a, pointset[i], b) > 0f64)
+        .collect()
+}
+
+#[cfg(feature = "parallel")]
+fn par_farthest(pointset: &[Point2], candidates: &[usize], a: usize, b: usize) -> usize {
+    // on ties the last index wins, like in the sequential fold
+    let (pa, pb) = (pointset[a], pointset[b]);
+    candidates.par_iter()
+        .cloned()
+        .reduce_with(|farthest, i| if cmp_orient2d(pa, pb, pointset[farthest], pointset[i]) == Less {farthest} else {i})
+        .unwrap_or(b)
+}
+
+fn push_vertex(pointset: &[Point2], b: usize, out: &mut Vec<usize>) {
+    let n = out.len();
+    // ensure no collinear points, but a segment has to turn back to its start
+    if n < 2 || orient2d(pointset[out[n-2]], pointset[out[n-1]], pointset[b]) > 0f64 || pointset[out[n-2]] == pointset[b] {
+        out.push(b);
+    } else {
+        out[n-1] = b;
+    }
+}
+
+#[cfg(not(feature = "visual"))]
+fn hull(pointset: &[Point2]) -> Vec<usize> {
+    if pointset.len() < 3 {
+        return (0..pointset.len()).collect()
+    }
+
+    let (min, max) = extrema(pointset);
+    // all points are identical
+    if pointset[min] == pointset[max] {
+        return vec![min]
+    }
+    let all: Vec<usize> = (0..pointset.len()).collect();
 
-    let mut hull: Vec<f64> = Vec::new();
+    let mut hull: Vec<usize> = Vec::new();
 
-    qh_recursion(pointset, min, max, &mut hull);
-    qh_recursion(pointset, max, min, &mut hull);
+    qh_recursion(pointset, &all, min, max, &mut hull);
+    qh_recursion(pointset, &all, max, min, &mut hull);
 
     hull
 }
 
 #[cfg(not(feature = "visual"))]
-fn qh_recursion(pointset: &[f64], a: (f64, f64), b: (f64, f64), out: &mut Vec<f64>) {
+fn qh_recursion(pointset: &[Point2], candidates: &[usize], a: usize, b: usize, out: &mut Vec<usize>) {
     // find left and farthest away point q
-    let left_of: Vec<f64> = pointset.iter()
-        .cloned()
-        .tuples::<(_, _)>()
-        .filter(|&i| cross2d(a, i, b) > 0f64)
-        .fold(Vec::new(), |mut acc, p| { acc.push(p.0); acc.push(p.1); acc });
+    let left_of = outside(pointset, candidates, a, b);
 
     // if there is none: add b to out and return
     if left_of.is_empty() {
-        let n = out.len();
-        // ensure no collinear points
-        if n < 4 || cross2d((out[n-4], out[n-3]), (out[n-2], out[n-1]), b) > 0f64 {
-            out.push(b.0);
-            out.push(b.1);
-        } else {
-            out[n-2] = b.0;
-            out[n-1] = b.1;
-        }
+        push_vertex(pointset, b, out);
     } else {
         // else recurse with the edge (a, q) and (q, b)
-        let q = left_of.iter()
-            .cloned()
-            .tuples::<(_, _)>()
-            .fold(b, |farthest: (f64, f64), i: (f64, f64)| if cross2d(a, farthest, b) > cross2d(a, i, b) {farthest} else {i});
+        let q = farthest(pointset, &left_of, a, b);
 
-        qh_recursion(&left_of, a, q, out);
-        qh_recursion(&left_of, q, b, out);
+        qh_recursion(pointset, &left_of, a, q, out);
+        qh_recursion(pointset, &left_of, q, b, out);
     }
 }
 
+#[cfg(feature = "parallel")]
+fn par_hull(pointset: &[Point2]) -> Vec<usize> {
+    if pointset.len() < 3 {
+        return (0..pointset.len()).collect()
+    }
+
+    let (min, max) = par_extrema(pointset);
+    // all points are identical
+    if pointset[min] == pointset[max] {
+        return vec![min]
+    }
+    let all: Vec<usize> = (0..pointset.len()).collect();
+
+    let (upper, lower) = rayon::join(
+        || par_recursion(pointset, &all, min, max),
+        || par_recursion(pointset, &all, max, min),
+    );
+
+    // the vertices arrive in the same order as in the sequential recursion
+    let mut hull: Vec<usize> = Vec::new();
+    for b in upper.into_iter().chain(lower) {
+        push_vertex(pointset, b, &mut hull);
+    }
+
+    hull
+}
+
+/// the ends `b` of all edges found left of the edge from `a` to `b`, in order
+#[cfg(feature = "parallel")]
+fn par_recursion(pointset: &[Point2], candidates: &[usize], a: usize, b: usize) -> Vec<usize> {
+    let parallel = candidates.len() >= PARALLEL_THRESHOLD;
+
+    let left_of = if parallel {
+        par_outside(pointset, candidates, a, b)
+    } else {
+        outside(pointset, candidates, a, b)
+    };
+
+    if left_of.is_empty() {
+        return vec![b]
+    }
+
+    let (mut first, second) = if parallel {
+        let q = par_farthest(pointset, &left_of, a, b);
+        rayon::join(
+            || par_recursion(pointset, &left_of, a, q),
+            || par_recursion(pointset, &left_of, q, b),
+        )
+    } else {
+        let q = farthest(pointset, &left_of, a, b);
+        (par_recursion(pointset, &left_of, a, q), par_recursion(pointset, &left_of, q, b))
+    };
+    first.extend(second);
+
+    first
+}
+
 #[cfg(feature = "visual")]
 use crate::visualization::SVG;
 
 #[cfg(feature = "visual")]
-pub fn quickhull(pointset: &[f64]) -> Vec<f64> {
-    if pointset.len() < 3*2 {
-        return pointset.to_vec()
+fn hull(pointset: &[Point2]) -> Vec<usize> {
+    if pointset.len() < 3 {
+        return (0..pointset.len()).collect()
     }
 
-    let start = (pointset[0], pointset[1]);
-    let (min, max) = pointset.iter()
-        .tuples::<(_, _)>()
-        .fold((start, start), |(min, max), (&x, &y)| {
-            let min = if x < min.0 { (x, y) } else { min };
-            let max = if x > max.0 { (x, y) } else { max };
-            (min, max)
-        });
+    let (min, max) = extrema(pointset);
+    // all points are identical
+    if pointset[min] == pointset[max] {
+        return vec![min]
+    }
+    let all: Vec<usize> = (0..pointset.len()).collect();
 
-    let mut hull: Vec<f64> = Vec::new();
+    let mut hull: Vec<usize> = Vec::new();
     let mut ctr = 0;
-    let mut all_lines: Vec<[f64; 4]> = Vec::new();
+    let mut all_lines: Vec<[Point2; 2]> = Vec::new();
 
     // fix for visualiszation
-    hull.push(min.0);
-    hull.push(min.1);
+    hull.push(min);
 
-    qh_recursion(pointset, min, max, &mut hull, pointset, &mut all_lines, &mut ctr);
-    qh_recursion(pointset, max, min, &mut hull, pointset, &mut all_lines, &mut ctr);
+    qh_recursion(pointset, &all, min, max, &mut hull, &mut all_lines, &mut ctr);
+    qh_recursion(pointset, &all, max, min, &mut hull, &mut all_lines, &mut ctr);
 
     // undo the vis-fix
-    hull.into_iter().skip(2).collect()
+    hull.into_iter().skip(1).collect()
 }
 
 #[cfg(feature = "visual")]
-fn qh_recursion(pointset: &[f64], a: (f64, f64), b: (f64, f64), out: &mut Vec<f64>, all: &[f64], all_lines: &mut Vec<[f64; 4]>, ctr: &mut u32) {
+fn qh_recursion(pointset: &[Point2], candidates: &[usize], a: usize, b: usize, out: &mut Vec<usize>, all_lines: &mut Vec<[Point2; 2]>, ctr: &mut u32) {
     *ctr += 1;
     // find left and farthest away point q
-    let left_of: Vec<f64> = pointset.iter()
-        .cloned()
-        .tuples::<(_, _)>()
-        .filter(|&i| cross2d(a, i, b) > 0f64)
-        .fold(Vec::new(), |mut acc, p| { acc.push(p.0); acc.push(p.1); acc });
+    let left_of = outside(pointset, candidates, a, b);
 
     let filename = format!("img/quickhull_{:04}.svg", ctr);
     let mut s = SVG::new();
-    s.points(all, "lightgray");
-    // points(&filename, pointset, "grey");
-    s.points(&left_of, "green");
+    s.points(pointset, "lightgray");
+    s.points(&gather(pointset, &left_of), "green");
 
     // if there is none: add b to out and return
     if left_of.is_empty() {
-        let n = out.len();
-        // ensure no collinear points
-        if n < 4 || cross2d((out[n-4], out[n-3]), (out[n-2], out[n-1]), b) > 0f64 {
-            out.push(b.0);
-            out.push(b.1);
-        } else {
-            out[n-2] = b.0;
-            out[n-1] = b.1;
-        }
+        push_vertex(pointset, b, out);
 
-        s.points(&[b.0, b.1], "red");
+        s.points(&[pointset[b]], "red");
         for l in all_lines.iter() {
             s.lines(l, "grey");
         }
-        let l: [f64; 4] = [a.0, a.1, b.0, b.1];
+        let l: [Point2; 2] = [pointset[a], pointset[b]];
         s.lines(&l, "red");
 
-        s.points(out, "black");
-        s.lines(out, "black");
+        s.points(&gather(pointset, out), "black");
+        s.lines(&gather(pointset, out), "black");
 
     } else {
         // else recurse with the edge (a, q) and (q, b)
-        let q = left_of.iter()
-            .cloned()
-            .tuples::<(_, _)>()
-            .fold(b, |farthest: (f64, f64), i: (f64, f64)| if cross2d(a, farthest, b) > cross2d(a, i, b) {farthest} else {i});
+        let q = farthest(pointset, &left_of, a, b);
 
-        s.points(&[q.0, q.1], "red");
+        s.points(&[pointset[q]], "red");
         for l in all_lines.iter() {
             s.lines(l, "grey");
         }
-        let l: [f64; 4] = [a.0, a.1, b.0, b.1];
+        let l: [Point2; 2] = [pointset[a], pointset[b]];
         s.lines(&l, "red");
         all_lines.push(l);
 
-        s.points(out, "black");
-        s.lines(out, "black");
+        s.points(&gather(pointset, out), "black");
+        s.lines(&gather(pointset, out), "black");
 
-        qh_recursion(&left_of, a, q, out, all, all_lines, ctr);
-        qh_recursion(&left_of, q, b, out, all, all_lines, ctr);
+        qh_recursion(pointset, &left_of, a, q, out, all_lines, ctr);
+        qh_recursion(pointset, &left_of, q, b, out, all_lines, ctr);
     }
 
     s.save(&filename).expect("io error");
@@ -158,25 +308,58 @@ fn qh_recursion(pointset: &[f64], a: (f64, f64), b: (f64, f64), out: &mut Vec<f6
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{util::tests::{check_2048, check_square}, akl};
+    use crate::{util::tests::{check_2048, check_square, check_square_indices, check_square_boundary}};
+    #[cfg(feature = "parallel")]
+    use crate::util::{get_test_vector_2d, tests::get_square};
+    use crate::{Quickhull, Akl};
 
     #[test]
     fn quickhull_square() {
-        check_square(quickhull);
+        check_square(&Quickhull);
     }
 
     #[test]
     fn quickhull_2048() {
-        check_2048(quickhull, "quickhull");
+        check_2048(&Quickhull);
     }
 
     #[test]
     fn quickhull_akl_square() {
-        check_square(|v| quickhull(&akl(v)));
+        check_square(&Akl(Quickhull));
     }
 
     #[test]
     fn quickhull_akl_2048() {
-        check_2048(|v| quickhull(&akl(v)), "quickhull_akl");
+        check_2048(&Akl(Quickhull));
+    }
+
+    #[test]
+    fn quickhull_square_boundary() {
+        check_square_boundary(&Quickhull);
+        check_square_boundary(&Akl(Quickhull));
+    }
+
+    #[test]
+    fn quickhull_indices_square() {
+        check_square_indices(quickhull_indices);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn par_quickhull_identical() {
+        use crate::util::get_test_vector_gaussian;
+
+        for v in [get_test_vector_2d(2048), get_test_vector_gaussian(100_000), get_square()] {
+            assert_eq!(par_quickhull(&v), quickhull(&v));
+        }
+
+        // many ties on a coarse grid
+        let grid: Vec<Point2> = (0..50_000)
+            .map(|i| Point2::new((i * 7 % 13) as f64, (i * 11 % 17) as f64))
+            .collect();
+        assert_eq!(par_quickhull_indices(&grid), quickhull_indices(&grid));
+
+        let identical = vec![Point2::new(0.5, 0.25); 5];
+        assert_eq!(par_quickhull_indices(&identical), vec![0]);
     }
-}
\ No newline at end of file
+}
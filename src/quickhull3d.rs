@@ -1,335 +1,425 @@
-use crate::d3::{Point3, Facet3, Edge3};
+use std::collections::HashMap;
+use std::cmp::Ordering::Greater;
 
-#[cfg(feature = "visual")]
-use crate::d3::threejs;
-
-fn divide_points_to_facets(pointset: &[Point3], facets: &[Facet3]) -> Vec<Vec<Point3>> {
-    let mut candidates: Vec<Vec<Point3>> = vec![Vec::new(); facets.len() + 1];
+use crate::d3::{Point3, Facet3};
+use crate::error::{HullError, validate_3d};
+use crate::predicates::{orient3d, cmp_orient3d, collinear3d};
+use crate::boundary::Boundary;
+use crate::mesh::HullMesh3;
+use crate::convex::ConvexPolytope;
 
-    for p in pointset {
-        let mut min_facet = 0;
-        let mut min_distance = 1e10;
-        for (n, f) in facets.iter().enumerate() {
-            // TODO can be precomputed
-            if !f.visible_from(p) {
-                continue
-            }
+#[cfg(feature = "visual")]
+use crate::d3::{threejs, Edge3};
+
+/// a triangle of the hull under construction
+///
+/// `neighbours[i]` is the face across the edge from `v[i]` to `v[i+1]` and
+/// `outside` holds the not yet processed points in front of the face
+struct Face {
+    v: [usize; 3],
+    neighbours: [usize; 3],
+    outside: Vec<usize>,
+    alive: bool,
+    /// the last iteration, in which the face was found to be visible
+    visited: usize,
+}
 
-            let m = f.mid();
-            let normal = f.normal();
-            let d = normal.dot(*p-m);
-            if d < min_distance {
-                min_distance = d;
-                min_facet = n + 1;
-            }
+impl Face {
+    fn new(v: [usize; 3]) -> Face {
+        Face {
+            v,
+            neighbours: [usize::MAX; 3],
+            outside: Vec::new(),
+            alive: true,
+            visited: 0,
         }
-        candidates[min_facet].push(*p);
     }
 
-    candidates
-}
+    fn visible_from(&self, pointset: &[Point3], p: usize) -> bool {
+        orient3d(pointset[self.v[0]], pointset[self.v[1]], pointset[self.v[2]], pointset[p]) > 0.
+    }
 
-fn farthest(facet: &Facet3, candidates: &[Point3]) -> Point3 {
-    let p = facet.mid();
-    let normal = facet.normal();
-    candidates.iter()
-        .cloned()
-        .fold(
-            p,
-            |farthest: Point3, i: Point3|
-                if normal.dot(farthest-p) > normal.dot(i-p) {
+    /// the point of the outside set furthest in front of the face
+    fn farthest(&self, pointset: &[Point3]) -> usize {
+        let [a, b, c] = self.v.map(|i| pointset[i]);
+        self.outside.iter()
+            .cloned()
+            .reduce(|farthest, i|
+                if cmp_orient3d(a, b, c, pointset[farthest], pointset[i]) == Greater {
                     farthest
                 } else {
                     i
                 }
-        )
-}
+            )
+            .expect("empty outside set")
+    }
 
-fn get_candidates_multiple(facets: &[Facet3], candidates: &[Point3]) -> Vec<Point3> {
-    candidates.iter()
-        .cloned()
-        .filter(|i| facets.iter().any(|f| f.visible_from(i)))
-        .collect()
+    #[cfg(feature = "visual")]
+    fn facet(&self, pointset: &[Point3]) -> Facet3 {
+        Facet3 { vertices: self.v.map(|i| pointset[i]) }
+    }
 }
 
-fn get_candidates(facet: &Facet3, candidates: &[Point3]) -> Vec<Point3> {
-    candidates.iter()
-        .cloned()
-        .filter(|i| facet.visible_from(i))
-        .collect()
+/// assigns every point to the outside set of the first of the `candidates`
+/// faces it is in front of, points in front of none are inside the hull
+fn partition(pointset: &[Point3], points: impl Iterator<Item = usize>, faces: &mut [Face], candidates: &[usize]) {
+    for p in points {
+        if let Some(&f) = candidates.iter().find(|&&f| faces[f].visible_from(pointset, p)) {
+            faces[f].outside.push(p);
+        }
+    }
 }
 
-#[cfg(not(feature = "visual"))]
-pub fn quickhull3d(pointset: &[Point3]) -> Vec<Facet3> {
-    // get a facet with all points on the hull
-    let start = pointset[0];
-    // FIXME: we need to ensure that all 3 points are distinct
-    let extrema = pointset.iter()
-        .fold([start; 6], |[max_x, max_y, max_z, min_x, min_y, min_z], &p| {
-            let max_x = if p.x > max_x.x { p } else { max_x };
-            let max_y = if p.y > max_y.y { p } else { max_y };
-            let max_z = if p.z > max_z.z { p } else { max_z };
-            let min_x = if p.x < min_x.x { p } else { min_x };
-            let min_y = if p.y < min_y.y { p } else { min_y };
-            let min_z = if p.z < min_z.z { p } else { min_z };
-            [max_x, max_y, max_z, min_x, min_y, min_z]
-        });
-
-    let mut hull: Vec<Facet3> = Vec::new();
-
-    let mut unique: Vec<Point3> = Vec::new();
-    'outer: for i in &extrema {
-        for j in &unique {
-            if *j == *i {
-                continue 'outer;
+/// the faces visible from `eye` by a depth-first search over the neighbours of
+/// the visible face `start`, and the horizon as the edges of visible faces
+/// `(u, v)` together with the invisible face behind them
+fn visible_region(pointset: &[Point3], faces: &mut [Face], start: usize, eye: usize, iteration: usize) -> (Vec<usize>, Vec<(usize, usize, usize)>) {
+    let mut visible = Vec::new();
+    let mut horizon = Vec::new();
+
+    faces[start].visited = iteration;
+    let mut stack = vec![start];
+    while let Some(f) = stack.pop() {
+        visible.push(f);
+        for i in 0..3 {
+            let n = faces[f].neighbours[i];
+            if faces[n].visited == iteration {
+                continue
+            }
+            if faces[n].visible_from(pointset, eye) {
+                faces[n].visited = iteration;
+                stack.push(n);
+            } else {
+                horizon.push((faces[f].v[i], faces[f].v[(i+1) % 3], n));
             }
         }
-        unique.push(*i);
-    }
-    if unique.len() < 3 {
-        panic!();
     }
 
-    let p1 = unique[0];
-    let p2 = unique[1];
-    let p3 = unique[2];
+    (visible, horizon)
+}
 
-    let f = Facet3 { vertices: [p1, p2, p3] };
-    let q = farthest(&f, pointset);
+/// connects the faces to the horizon and among each other, the new face
+/// of every horizon edge `(u, v)` is `(u, v, eye)`
+fn add_cone(faces: &mut Vec<Face>, horizon: &[(usize, usize, usize)], eye: usize) -> Vec<usize> {
+    let first = faces.len();
+    let mut starting_at = HashMap::new();
+
+    for (k, &(u, v, behind)) in horizon.iter().enumerate() {
+        let mut face = Face::new([u, v, eye]);
+        face.neighbours[0] = behind;
+        faces.push(face);
+        starting_at.insert(u, first + k);
+
+        // the face behind the horizon sees this edge as (v, u)
+        let b = &mut faces[behind];
+        let j = (0..3).find(|&j| b.v[j] == v && b.v[(j+1) % 3] == u).expect("broken adjacency");
+        b.neighbours[j] = first + k;
+    }
 
-    let f1 = Facet3 { vertices: [p1, p3, p2] };
-    let f2 = Facet3 { vertices: [p1, p2, q] };
-    let f3 = Facet3 { vertices: [p2, p3, q] };
-    let f4 = Facet3 { vertices: [p3, p1, q] };
-    let facets = [f1.clone(), f2.clone(), f3.clone(), f4.clone()];
+    // the horizon is a cycle, so the edge (v, eye) borders the face starting at v
+    // and (eye, u) the face ending at u
+    for k in first..faces.len() {
+        let next = starting_at[&faces[k].v[1]];
+        faces[k].neighbours[1] = next;
+        faces[next].neighbours[2] = k;
+    }
 
-    // initial tetrahedron
-    hull.push(f1.clone());
-    hull.push(f2.clone());
-    hull.push(f3.clone());
-    hull.push(f4.clone());
+    (first..faces.len()).collect()
+}
 
-    let candidates = divide_points_to_facets(pointset, &facets);
+/// the initial tetrahedron with its four faces linked to each other
+fn initial_faces(pointset: &[Point3]) -> Vec<Face> {
+    let ([p1, p2, p3], q) = initial_simplex(pointset);
+
+    let mut faces = vec![
+        Face::new([p1, p3, p2]),
+        Face::new([p1, p2, q]),
+        Face::new([p2, p3, q]),
+        Face::new([p3, p1, q]),
+    ];
+
+    // every directed edge appears reversed in exactly one other face
+    for f in 0..4 {
+        for i in 0..3 {
+            let (a, b) = (faces[f].v[i], faces[f].v[(i+1) % 3]);
+            let n = (0..4)
+                .find(|&g| (0..3).any(|j| faces[g].v[j] == b && faces[g].v[(j+1) % 3] == a))
+                .expect("broken tetrahedron");
+            faces[f].neighbours[i] = n;
+        }
+    }
 
-    // FIXME do not give the whole pointset but disjunct subsets
-    quickhull3d_recursion(&candidates[1], &f1, &mut hull, pointset);
-    quickhull3d_recursion(&candidates[2], &f2, &mut hull, pointset);
-    quickhull3d_recursion(&candidates[3], &f3, &mut hull, pointset);
-    quickhull3d_recursion(&candidates[4], &f4, &mut hull, pointset);
+    let simplex = [p1, p2, p3, q];
+    let others = (0..pointset.len()).filter(|i| !simplex.contains(i));
+    partition(pointset, others, &mut faces, &[0, 1, 2, 3]);
 
-    hull
+    faces
 }
 
-#[cfg(not(feature = "visual"))]
-fn quickhull3d_recursion(candidates: &[Point3], facet: &Facet3, out: &mut Vec<Facet3>, all_points: &[Point3]) {
-    let in_front_of = get_candidates(facet, candidates);
+fn key(p: &Point3) -> [u64; 3] {
+    [p.x.to_bits(), p.y.to_bits(), p.z.to_bits()]
+}
 
-    // if there are still candidates continue, else we are finished
-    if in_front_of.is_empty() {
-        return
-    }
-    // if the facet was removed from out in the meantime, we do not need to test it
-    if !out.contains(facet) {
-        return
+/// index of the first occurrence of every point in `pointset`
+fn first_indices(pointset: &[Point3]) -> HashMap<[u64; 3], usize> {
+    let mut index = HashMap::new();
+    for (n, p) in pointset.iter().enumerate() {
+        index.entry(key(p)).or_insert(n);
     }
+    index
+}
 
-    // pay attention to orientation: normal must point outside
-    let q = farthest(facet, &in_front_of);
-
-    // q is eye point, search for the horizon
-    // delete all facets inside the horizon
-    // make facets from the horizon edges to the eye point
-
-    // FIXME: this implementation is slow and should be replaced by a DFS or similar
-    // FIXME: to speed it up slightly, replace the Vec out by a set or something
-    // Just test for every facet if it is visible from the eye point,
-    // i.e., if it is, remove it and remember all edges
-    // every edge which is remembered only once, is part of the horizon
-    let mut visible_facets: Vec<Facet3> = Vec::new();
-    for f in out.iter() {
-        if f.visible_from(&q) {
-            visible_facets.push(f.clone())
-        }
+/// facets as triples of indices into `pointset`, counter-clockwise seen from outside
+///
+/// # Panics
+/// if the points do not span a volume, see `try_quickhull3d_indices` for a fallible version
+pub fn quickhull3d_indices(pointset: &[Point3]) -> Vec<[usize; 3]> {
+    match try_quickhull3d_indices(pointset) {
+        Ok(hull) => hull,
+        Err(e) => panic!("quickhull3d: {}", e),
     }
+}
 
-    // extract all edges (in correct orientation) from the visible facets
-    let mut all_edges: Vec<Edge3> = Vec::new();
-    for f in visible_facets.iter() {
-        let v1 = f.vertices[0];
-        let v2 = f.vertices[1];
-        let v3 = f.vertices[2];
-        all_edges.push(Edge3 { vertices: [v1, v2] });
-        all_edges.push(Edge3 { vertices: [v2, v3] });
-        all_edges.push(Edge3 { vertices: [v3, v1] });
-        out.retain(|x| x != f);
-    }
+pub fn try_quickhull3d_indices(pointset: &[Point3]) -> Result<Vec<[usize; 3]>, HullError> {
+    validate_3d(pointset)?;
+    Ok(hull(pointset))
+}
 
-    let mut horizon: Vec<Edge3> = Vec::new();
-    for i in all_edges.iter() {
-        if all_edges.iter().filter(|&x| x == i).count() == 1 {
-            horizon.push(i.clone());
-        }
-    }
+/// indices of the points on the hull in ascending order, only the corners
+/// or with `Boundary::All` every point on a facet, duplicates are reported
+/// once by their first occurrence
+///
+/// with `Boundary::All` every point is located exactly by
+/// `ConvexPolytope::on_boundary`
+pub fn quickhull3d_boundary(pointset: &[Point3], boundary: Boundary) -> Vec<usize> {
+    let facets = quickhull3d_indices(pointset);
+    let index = first_indices(pointset);
+    let on_plane = |f: &[usize; 3], p: Point3| {
+        orient3d(pointset[f[0]], pointset[f[1]], pointset[f[2]], p) == 0.
+    };
+
+    match boundary {
+        Boundary::Extreme => {
+            let mut incident: Vec<Vec<usize>> = vec![Vec::new(); pointset.len()];
+            for (n, f) in facets.iter().enumerate() {
+                for &i in f {
+                    incident[i].push(n);
+                }
+            }
 
-    // facets generated in this iteration of the recursion
-    let mut new_facets = Vec::new();
-    for e in horizon.iter() {
-        let f = Facet3 { vertices: [e.vertices[0], e.vertices[1], q] };
-        out.push(f.clone());
-        new_facets.push(f);
+            // a corner has incident facets in at least three different planes,
+            // a point inside of an edge only in two and inside of a face in one
+            let mut corners: Vec<usize> = (0..pointset.len())
+                .filter(|&i| {
+                    let mut planes: Vec<usize> = Vec::new();
+                    for &n in &incident[i] {
+                        let f = &facets[n];
+                        if !planes.iter().any(|&m| f.iter().all(|&j| on_plane(&facets[m], pointset[j]))) {
+                            planes.push(n);
+                        }
+                    }
+                    planes.len() >= 3
+                })
+                // the facets may use any copy of a duplicate
+                .map(|i| index[&key(&pointset[i])])
+                .collect();
+            corners.sort_unstable();
+            corners.dedup();
+            corners
+        }
+        Boundary::All => {
+            let polytope = ConvexPolytope::new(facets.iter()
+                .map(|f| Facet3 { vertices: f.map(|i| pointset[i]) })
+                .collect());
+            (0..pointset.len())
+                .filter(|&i| index[&key(&pointset[i])] == i)
+                .filter(|&i| polytope.on_boundary(pointset[i]))
+                .collect()
+        }
     }
+}
 
-    // calculate for every candidate point the nearest facet
-    // this way every point will only occur in one subtree of the recursion
-    // FIXME we are testing far too many points. it we should discard interior points
-    let possible = get_candidates_multiple(&new_facets, all_points);
-    let candidates = divide_points_to_facets(&possible, &new_facets);
+/// get a non-degenerate tetrahedron of input points as indices of a base
+/// triangle, whose normal points towards the apex
+/// the input has to span a volume, see `validate_3d`
+fn initial_simplex(pointset: &[Point3]) -> ([usize; 3], usize) {
+    // start from extremal points, since they are on the hull
+    let extrema = (0..pointset.len())
+        .fold([0; 6], |[max_x, max_y, max_z, min_x, min_y, min_z], i| {
+            let p = pointset[i];
+            let max_x = if p.x > pointset[max_x].x { i } else { max_x };
+            let max_y = if p.y > pointset[max_y].y { i } else { max_y };
+            let max_z = if p.z > pointset[max_z].z { i } else { max_z };
+            let min_x = if p.x < pointset[min_x].x { i } else { min_x };
+            let min_y = if p.y < pointset[min_y].y { i } else { min_y };
+            let min_z = if p.z < pointset[min_z].z { i } else { min_z };
+            [max_x, max_y, max_z, min_x, min_y, min_z]
+        });
 
-    for (n, f) in new_facets.iter().enumerate() {
-        quickhull3d_recursion(&candidates[n+1], f, out, all_points);
+    let p1 = extrema[0];
+    let p2 = extrema.iter()
+        .cloned()
+        .find(|&p| pointset[p] != pointset[p1])
+        .expect("all points coincide");
+    let (a, b) = (pointset[p1], pointset[p2]);
+
+    // the extrema may all be on one line, so take the point furthest from it
+    // the distances are only approximate, so fall back to any point which is
+    // exactly off the line
+    let dir = b - a;
+    let off_line = |&i: &usize| !collinear3d(a, b, pointset[i]);
+    let p3 = (0..pointset.len())
+        .max_by(|&i, &j| {
+            let di = dir.cross(pointset[i] - a).length();
+            let dj = dir.cross(pointset[j] - a).length();
+            di.total_cmp(&dj)
+        })
+        .filter(off_line)
+        .or_else(|| (0..pointset.len()).find(off_line))
+        .expect("all points are collinear");
+    let c = pointset[p3];
+
+    let normal = dir.cross(c - a);
+    let off_plane = |&i: &usize| orient3d(a, b, c, pointset[i]) != 0.;
+    let q = (0..pointset.len())
+        .max_by(|&i, &j| normal.dot(pointset[i] - a).abs().total_cmp(&normal.dot(pointset[j] - a).abs()))
+        .filter(off_plane)
+        .or_else(|| (0..pointset.len()).find(off_plane))
+        .expect("all points are coplanar");
+
+    if orient3d(a, b, c, pointset[q]) > 0. {
+        ([p1, p2, p3], q)
+    } else {
+        ([p1, p3, p2], q)
     }
 }
 
-#[cfg(feature = "visual")]
+/// facets of the hull, counter-clockwise seen from outside
+///
+/// # Panics
+/// if the points do not span a volume, see `try_quickhull3d` for a fallible version
 pub fn quickhull3d(pointset: &[Point3]) -> Vec<Facet3> {
-    // get a facet with all points on the hull
-    let start = pointset[0];
-    // FIXME: we need to ensure that all 3 points are distinct
-    let extrema = pointset.iter()
-        .fold([start; 6], |[max_x, max_y, max_z, min_x, min_y, min_z], &p| {
-            let max_x = if p.x > max_x.x { p } else { max_x };
-            let max_y = if p.y > max_y.y { p } else { max_y };
-            let max_z = if p.z > max_z.z { p } else { max_z };
-            let min_x = if p.x < min_x.x { p } else { min_x };
-            let min_y = if p.y < min_y.y { p } else { min_y };
-            let min_z = if p.z < min_z.z { p } else { min_z };
-            [max_x, max_y, max_z, min_x, min_y, min_z]
-        });
+    match try_quickhull3d(pointset) {
+        Ok(hull) => hull,
+        Err(e) => panic!("quickhull3d: {}", e),
+    }
+}
 
-    let mut hull: Vec<Facet3> = Vec::new();
+pub fn try_quickhull3d(pointset: &[Point3]) -> Result<Vec<Facet3>, HullError> {
+    validate_3d(pointset)?;
+    Ok(hull(pointset).into_iter()
+        .map(|v| Facet3 { vertices: v.map(|i| pointset[i]) })
+        .collect())
+}
 
-    let mut unique: Vec<Point3> = Vec::new();
-    'outer: for i in &extrema {
-        for j in &unique {
-            if *j == *i {
-                continue 'outer;
-            }
-        }
-        unique.push(*i);
-    }
-    if unique.len() < 3 {
-        panic!();
+/// the hull as a half-edge mesh with shared vertices
+///
+/// # Panics
+/// if the points do not span a volume, see `try_quickhull3d_mesh` for a fallible version
+pub fn quickhull3d_mesh(pointset: &[Point3]) -> HullMesh3 {
+    match try_quickhull3d_mesh(pointset) {
+        Ok(mesh) => mesh,
+        Err(e) => panic!("quickhull3d: {}", e),
     }
+}
 
-    let p1 = unique[0];
-    let p2 = unique[1];
-    let p3 = unique[2];
-
-    let f = Facet3 { vertices: [p1, p2, p3] };
-    let q = farthest(&f, pointset);
+pub fn try_quickhull3d_mesh(pointset: &[Point3]) -> Result<HullMesh3, HullError> {
+    validate_3d(pointset)?;
+    Ok(HullMesh3::from_triangles(pointset, &hull(pointset)))
+}
 
-    let f1 = Facet3 { vertices: [p1, p3, p2] };
-    let f2 = Facet3 { vertices: [p1, p2, q] };
-    let f3 = Facet3 { vertices: [p2, p3, q] };
-    let f4 = Facet3 { vertices: [p3, p1, q] };
-    let facets = [f1.clone(), f2.clone(), f3.clone(), f4.clone()];
+// Barber, Dobkin and Huhdanpaa, "The Quickhull Algorithm for Convex Hulls",
+// ACM Transactions on Mathematical Software 22, 469 (1996).
+//
+// Every point is assigned to one face it is in front of (the conflict graph).
+// The farthest point of a face is added to the hull, its visible region is
+// replaced by a cone of new faces, and only the points assigned to the
+// removed faces have to be tested against the new ones. Points in front of
+// none of them are inside the hull and discarded for good.
 
-    // initial tetrahedron
-    hull.push(f1.clone());
-    hull.push(f2.clone());
-    hull.push(f3.clone());
-    hull.push(f4.clone());
+#[cfg(not(feature = "visual"))]
+fn hull(pointset: &[Point3]) -> Vec<[usize; 3]> {
+    let mut faces = initial_faces(pointset);
+    let mut pending: Vec<usize> = (0..faces.len()).collect();
+    let mut iteration = 0;
+
+    while let Some(f) = pending.pop() {
+        if !faces[f].alive || faces[f].outside.is_empty() {
+            continue
+        }
+        iteration += 1;
 
-    let candidates = divide_points_to_facets(pointset, &facets);
+        let eye = faces[f].farthest(pointset);
+        let (visible, horizon) = visible_region(pointset, &mut faces, f, eye, iteration);
 
-    let mut ctr = 0;
+        let mut orphans = Vec::new();
+        for &v in visible.iter() {
+            faces[v].alive = false;
+            orphans.append(&mut faces[v].outside);
+        }
 
-    // FIXME do not give the whole pointset but disjunct subsets
-    quickhull3d_recursion(&candidates[1], &f1, &mut hull, pointset, &mut ctr);
-    quickhull3d_recursion(&candidates[2], &f2, &mut hull, pointset, &mut ctr);
-    quickhull3d_recursion(&candidates[3], &f3, &mut hull, pointset, &mut ctr);
-    quickhull3d_recursion(&candidates[4], &f4, &mut hull, pointset, &mut ctr);
+        let cone = add_cone(&mut faces, &horizon, eye);
+        partition(pointset, orphans.into_iter().filter(|&p| p != eye), &mut faces, &cone);
+        pending.extend(cone);
+    }
 
-    hull
+    faces.iter()
+        .filter(|f| f.alive)
+        .map(|f| f.v)
+        .collect()
 }
 
 #[cfg(feature = "visual")]
-fn quickhull3d_recursion(candidates: &[Point3], facet: &Facet3, out: &mut Vec<Facet3>, all_points: &[Point3], ctr: &mut u32) {
-    let in_front_of = get_candidates(facet, candidates);
-
-    // if there are still candidates continue, else we are finished
-    if in_front_of.is_empty() {
-        return
-    }
-    // if the facet was removed from out in the meantime, we do not need to test it
-    if !out.contains(facet) {
-        return
-    }
-
-    // pay attention to orientation: normal must point outside
-    let q = farthest(facet, &in_front_of);
-
-    // q is eye point, search for the horizon
-    // delete all facets inside the horizon
-    // make facets from the horizon edges to the eye point
-
-    // FIXME: this implementation is slow and should be replaced by a DFS or similar
-    // FIXME: to speed it up slightly, replace the Vec out by a set or something
-    // Just test for every facet if it is visible from the eye point,
-    // i.e., if it is, remove it and remember all edges
-    // every edge which is remembered only once, is part of the horizon
-    let mut visible_facets: Vec<Facet3> = Vec::new();
-    for f in out.iter() {
-        if f.visible_from(&q) {
-            visible_facets.push(f.clone())
+fn hull(pointset: &[Point3]) -> Vec<[usize; 3]> {
+    let mut faces = initial_faces(pointset);
+    let mut pending: Vec<usize> = (0..faces.len()).collect();
+    let mut iteration = 0;
+
+    while let Some(f) = pending.pop() {
+        if !faces[f].alive || faces[f].outside.is_empty() {
+            continue
         }
-    }
+        iteration += 1;
 
-    // extract all edges (in correct orientation) from the visible facets
-    let mut all_edges: Vec<Edge3> = Vec::new();
-    for f in visible_facets.iter() {
-        let v1 = f.vertices[0];
-        let v2 = f.vertices[1];
-        let v3 = f.vertices[2];
-        all_edges.push(Edge3 { vertices: [v1, v2] });
-        all_edges.push(Edge3 { vertices: [v2, v3] });
-        all_edges.push(Edge3 { vertices: [v3, v1] });
-        out.retain(|x| x != f);
-    }
+        let eye = faces[f].farthest(pointset);
+        let in_front_of: Vec<Point3> = faces[f].outside.iter().map(|&i| pointset[i]).collect();
+        let (visible, horizon) = visible_region(pointset, &mut faces, f, eye, iteration);
 
-    let mut horizon: Vec<Edge3> = Vec::new();
-    for i in all_edges.iter() {
-        if all_edges.iter().filter(|&x| x == i).count() == 1 {
-            horizon.push(i.clone());
+        let mut orphans = Vec::new();
+        for &v in visible.iter() {
+            faces[v].alive = false;
+            orphans.append(&mut faces[v].outside);
         }
-    }
 
-    *ctr += 1;
-    threejs(all_points, out, &q, &in_front_of, &visible_facets, &horizon, &format!("quickhull3d_{}.html", ctr)).expect("io error");
-
-    // facets generated in this iteration of the recursion
-    let mut new_facets = Vec::new();
-    for e in horizon.iter() {
-        let f = Facet3 { vertices: [e.vertices[0], e.vertices[1], q] };
-        out.push(f.clone());
-        new_facets.push(f);
+        let remaining: Vec<Facet3> = faces.iter()
+            .filter(|f| f.alive)
+            .map(|f| f.facet(pointset))
+            .collect();
+        let visible_facets: Vec<Facet3> = visible.iter()
+            .map(|&v| faces[v].facet(pointset))
+            .collect();
+        let horizon_edges: Vec<Edge3> = horizon.iter()
+            .map(|&(u, v, _)| Edge3 { vertices: [pointset[u], pointset[v]] })
+            .collect();
+        threejs(pointset, &remaining, &pointset[eye], &in_front_of, &visible_facets, &horizon_edges, &format!("quickhull3d_{}.html", iteration)).expect("io error");
+
+        let cone = add_cone(&mut faces, &horizon, eye);
+        partition(pointset, orphans.into_iter().filter(|&p| p != eye), &mut faces, &cone);
+        pending.extend(cone);
     }
 
-    // calculate for every candidate point the nearest facet
-    // this way every point will only occur in one subtree of the recursion
-    // FIXME we are testing far too many points. it we should discard interior points
-    let possible = get_candidates_multiple(&new_facets, all_points);
-    let candidates = divide_points_to_facets(&possible, &new_facets);
-
-    for (n, f) in new_facets.iter().enumerate() {
-        quickhull3d_recursion(&candidates[n+1], f, out, all_points, ctr);
-    }
+    faces.iter()
+        .filter(|f| f.alive)
+        .map(|f| f.v)
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::util::tests::{check_3d_80, check_cube, check_simple_cube};
+    use crate::util::is_convex;
+    use crate::util::get_test_vector_gaussian_3d;
+    use crate::util::tests::{check_3d_80, check_cube, check_simple_cube, get_cube, get_simple_cube, get_random_lattice_3d};
 
     #[test]
     fn quickhull3d_simple_cube() {
@@ -345,4 +435,149 @@ mod tests {
     fn quickhull3d_80() {
         check_3d_80(quickhull3d);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn quickhull3d_collinear_extrema() {
+        // all six extrema are one of the two first points
+        let v = vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 1.0),
+            Point3::new(0.5, 0.5, 0.0),
+            Point3::new(0.5, 0.0, 0.5),
+        ];
+        let hull = quickhull3d(&v);
+
+        assert!(is_convex(&hull, &v));
+        assert_eq!(hull.len(), 4);
+    }
+
+    #[test]
+    fn quickhull3d_degenerate() {
+        let mut v = get_simple_cube();
+        v.retain(|p| p.z == 0.);
+        assert_eq!(try_quickhull3d(&v), Err(HullError::AllCoplanar));
+        assert_eq!(try_quickhull3d(&v[..1]), Err(HullError::TooFewPoints { found: 1, required: 4 }));
+    }
+
+    #[test]
+    fn quickhull3d_indices_cube() {
+        let v = get_cube();
+        let facets = quickhull3d_indices(&v);
+
+        assert_eq!(facets.len(), 12);
+        // only the 8 corners of the outer cube are vertices
+        assert!(facets.iter().flatten().all(|&i| i < 8));
+        let resolved: Vec<Facet3> = facets.iter()
+            .map(|f| Facet3 { vertices: f.map(|i| v[i]) })
+            .collect();
+        assert!(is_convex(&resolved, &v));
+    }
+
+    #[test]
+    fn quickhull3d_scaled() {
+        // tiny and huge coordinates must not change the combinatorics
+        for scale in [1e-30, 1e-6, 1e6, 1e30] {
+            let v: Vec<Point3> = get_cube().into_iter().map(|p| p / (1. / scale)).collect();
+            let hull = quickhull3d(&v);
+
+            assert_eq!(hull.len(), 12);
+            assert!(is_convex(&hull, &v));
+        }
+    }
+
+    #[test]
+    fn quickhull3d_boundary_cube() {
+        let mut v = get_simple_cube();
+        let corners = v.len();
+        v.extend([
+            // face centres and an edge midpoint
+            Point3::new(0.5, 0.5, 0.0),
+            Point3::new(0.5, 0.5, 1.0),
+            Point3::new(0.0, 0.5, 0.5),
+            Point3::new(1.0, 0.5, 0.5),
+            Point3::new(0.5, 0.0, 0.5),
+            Point3::new(0.5, 1.0, 0.5),
+            Point3::new(0.5, 0.0, 0.0),
+            // interior point and duplicate of a face centre
+            Point3::new(0.5, 0.5, 0.5),
+            Point3::new(0.5, 0.5, 0.0),
+        ]);
+
+        assert_eq!(quickhull3d_boundary(&v, Boundary::Extreme), (0..corners).collect::<Vec<_>>());
+        assert_eq!(quickhull3d_boundary(&v, Boundary::All), (0..corners+7).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn quickhull3d_boundary_random_lattice() {
+        for seed in 0..500 {
+            let v = get_random_lattice_3d(seed, 10 + seed as usize % 50, 2);
+            let Ok(facets) = try_quickhull3d_indices(&v) else { continue };
+            let first = |i: usize| v.iter().position(|&q| q == v[i]).unwrap();
+
+            // every point on the plane of a facet, which contains it
+            let all: Vec<usize> = (0..v.len())
+                .filter(|&i| first(i) == i)
+                .filter(|&i| facets.iter().any(|f| orient3d(v[f[0]], v[f[1]], v[f[2]], v[i]) == 0.))
+                .collect();
+            assert_eq!(quickhull3d_boundary(&v, Boundary::All), all, "seed {}", seed);
+
+            // the corners of the merged faces
+            let mesh = quickhull3d_mesh(&v).merge_coplanar(0.);
+            let mut corners: Vec<usize> = mesh.indices.iter().map(|&i| first(i)).collect();
+            corners.sort_unstable();
+            assert_eq!(quickhull3d_boundary(&v, Boundary::Extreme), corners, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn quickhull3d_boundary_lattice() {
+        // a sheared lattice octahedron, whose facets and edges hold many points
+        let mut v = Vec::new();
+        for x in -5i32..=5 {
+            for y in -5i32..=5 {
+                for z in -5i32..=5 {
+                    if x.abs() + y.abs() + z.abs() <= 5 {
+                        v.push(Point3::new((x + y) as f64, (y - 2 * z) as f64, z as f64));
+                    }
+                }
+            }
+        }
+
+        let facets = quickhull3d_indices(&v);
+        let expected: Vec<usize> = (0..v.len())
+            .filter(|&i| facets.iter().any(|f| orient3d(v[f[0]], v[f[1]], v[f[2]], v[i]) == 0.))
+            .collect();
+        assert_eq!(quickhull3d_boundary(&v, Boundary::All), expected);
+        // all points with |x| + |y| + |z| = 5
+        assert_eq!(expected.len(), 4 * 5 * 5 + 2);
+    }
+
+    #[test]
+    fn quickhull3d_many() {
+        let v = get_test_vector_gaussian_3d(20000);
+        let hull = quickhull3d(&v);
+
+        assert!(is_convex(&hull, &v));
+        // closed surface of triangles: 3F = 2E and V - E + F = 2
+        let mut vertices: Vec<usize> = quickhull3d_indices(&v).into_iter().flatten().collect();
+        vertices.sort_unstable();
+        vertices.dedup();
+        assert_eq!(vertices.len() + hull.len() / 2, 2 + hull.len());
+    }
+
+    #[test]
+    fn quickhull3d_mesh_cube() {
+        let v = get_cube();
+        let mesh = quickhull3d_mesh(&v);
+
+        assert_eq!(mesh.num_vertices(), 8);
+        assert_eq!(mesh.num_edges(), 18);
+        assert_eq!(mesh.num_faces(), 12);
+        assert_eq!(mesh.euler_characteristic(), 2);
+        assert!(mesh.indices.iter().all(|&i| i < 8));
+        assert!(is_convex(&mesh.facets(), &v));
+
+        let v = get_test_vector_gaussian_3d(2000);
+        assert_eq!(quickhull3d_mesh(&v).euler_characteristic(), 2);
+    }
+}
@@ -0,0 +1,121 @@
+use std::f64::consts::PI;
+
+use crate::d2::Point2;
+use crate::primitives::{moments, perimeter_points};
+use crate::calipers::{diameter, min_width};
+
+/// shape descriptors of a 2D hull
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HullStats2d {
+    pub area: f64,
+    pub perimeter: f64,
+    /// number of points of the hull, including points on its edges, if
+    /// the hull was constructed with `Boundary::All`
+    pub vertices: usize,
+    /// largest distance between two points of the hull
+    pub diameter: f64,
+    /// smallest distance between two parallel lines enclosing the hull
+    pub width: f64,
+    /// isoperimetric ratio `4 pi A / P^2`, which is 1 only for a circle
+    pub circularity: f64,
+    /// centroid of the area
+    pub centroid: Point2,
+    /// `(l1 - l2)^2 / (l1 + l2)^2` of the eigenvalues of the gyration
+    /// tensor of the area, 0 for rotationally symmetric shapes
+    pub asphericity: f64,
+}
+
+impl HullStats2d {
+    /// statistics of a hull in counter-clockwise order, as returned by all
+    /// 2D algorithms, in O(h)
+    pub fn new(hull: &[Point2]) -> HullStats2d {
+        let o = hull.first().cloned().unwrap_or(Point2::new(0., 0.));
+        let (area, centroid, second) = moments(hull, o);
+        let perimeter = perimeter_points(hull);
+
+        // gyration tensor with respect to the centroid
+        let (gxx, gyy, gxy) = if area > 0. {
+            (
+                second[0] / area - centroid.x * centroid.x,
+                second[1] / area - centroid.y * centroid.y,
+                second[2] / area - centroid.x * centroid.y,
+            )
+        } else {
+            (0., 0., 0.)
+        };
+        // (l1 - l2)^2 = (gxx - gyy)^2 + 4 gxy^2 and l1 + l2 = gxx + gyy
+        let trace = gxx + gyy;
+        let asphericity = if trace > 0. {
+            ((gxx - gyy).powi(2) + 4. * gxy * gxy) / (trace * trace)
+        } else {
+            0.
+        };
+
+        HullStats2d {
+            area,
+            perimeter,
+            vertices: hull.len(),
+            diameter: diameter(hull),
+            width: min_width(hull).map_or(0., |w| w.width),
+            circularity: if perimeter > 0. { 4. * PI * area / (perimeter * perimeter) } else { 0. },
+            centroid: centroid + o,
+            asphericity,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+    use crate::d2::from_flat;
+    use crate::algorithm::{Algorithm, ConvexHull2d};
+    use crate::util::get_test_vector_2d;
+    use crate::util::tests::get_square;
+
+    #[test]
+    fn square_stats() {
+        let v = from_flat(&get_square());
+        for a in Algorithm::ALL {
+            let stats = HullStats2d::new(&a.hull(&v));
+
+            assert_approx_eq!(stats.area, 1.);
+            assert_approx_eq!(stats.perimeter, 4.);
+            assert_eq!(stats.vertices, 4);
+            assert_approx_eq!(stats.diameter, 2f64.sqrt());
+            assert_approx_eq!(stats.width, 1.);
+            assert_approx_eq!(stats.circularity, PI / 4.);
+            assert_approx_eq!(stats.centroid.x, 0.5);
+            assert_approx_eq!(stats.centroid.y, 0.5);
+            assert_approx_eq!(stats.asphericity, 0.);
+        }
+    }
+
+    #[test]
+    fn rectangle_stats() {
+        let p = Point2::new;
+        // rotated by 45 degrees, which must not change the asphericity
+        let rectangle = [p(0., 0.), p(2., 2.), p(1., 3.), p(-1., 1.)];
+        let stats = HullStats2d::new(&rectangle);
+
+        assert_approx_eq!(stats.area, 4.);
+        assert_approx_eq!(stats.width, 2f64.sqrt());
+        assert_approx_eq!(stats.diameter, 10f64.sqrt());
+        // eigenvalues 8/12 and 2/12 of the gyration tensor
+        assert_approx_eq!(stats.asphericity, 9. / 25.);
+    }
+
+    #[test]
+    fn stats_agree() {
+        let v = from_flat(&get_test_vector_2d(512));
+        let expected = HullStats2d::new(&Algorithm::Andrew.hull(&v));
+        for a in Algorithm::ALL {
+            let stats = HullStats2d::new(&a.hull(&v));
+            assert_eq!(stats.vertices, expected.vertices);
+            assert_approx_eq!(stats.area, expected.area);
+            assert_approx_eq!(stats.perimeter, expected.perimeter);
+            assert_approx_eq!(stats.diameter, expected.diameter);
+            assert_approx_eq!(stats.width, expected.width);
+        }
+    }
+}
@@ -41,7 +41,7 @@ pub fn get_test_vector_3d(n: usize) -> Vec<Point3> {
     let rng: Pcg64 = SeedableRng::seed_from_u64(seed);
     let uniform = Uniform::new(0.0, 1.0);
     rng.sample_iter(uniform)
-        .map(|a| (a*100.))
+        .map(|a| a*100.)
         .take(n * 3)
         .tuples()
         .map(|(x, y, z)| Point3::new(x, y, z))
@@ -53,7 +53,7 @@ pub fn get_test_vector_gaussian_3d(n: usize) -> Vec<Point3> {
     let rng: Pcg64 = SeedableRng::seed_from_u64(seed);
     let normal = Normal::new(0.0, 0.2).unwrap();
     rng.sample_iter(normal)
-        .map(|a| (a*100.))
+        .map(|a| a*100.)
         .take(n * 3)
         .tuples()
         .map(|(x, y, z)| Point3::new(x, y, z))
@@ -81,37 +81,97 @@ pub mod tests {
 
     use crate::Facet3;
     use crate::Point3;
+    use crate::{Point2, from_flat};
+    use crate::{ConvexHull2d, Boundary};
 
-    use crate::{area, surface};
+    use crate::{area_points, surface};
+    #[cfg(feature = "visual")]
+    use crate::to_flat;
 
     #[cfg(feature = "visual")]
     use crate::svg;
 
     const TEST_AREA_2D: f64 = 0.9887111601582999;
-    const TEST_AREA_2D_POINTS: usize = 38;
+    const TEST_AREA_2D_POINTS: usize = 19;
     const TEST_AREA_3D: f64 = 40322.038417123375;
     const TEST_AREA_3D_POINTS: usize = 50;
 
-    pub(crate) fn check_2048(algo: fn(&[f64]) -> Vec<f64>, name: &str) {
-        let v = get_test_vector_2d(2048);
+    pub(crate) fn check_2048(algo: &dyn ConvexHull2d) {
+        let v = from_flat(&get_test_vector_2d(2048));
 
-        let hull = algo(&v);
-        #[cfg(feature = "visual")] svg(&v, &hull, name).expect("io error");
+        let hull = algo.hull(&v);
+        #[cfg(feature = "visual")] svg(&to_flat(&v), &to_flat(&hull), &algo.name().replace('+', "_")).expect("io error");
 
         assert_eq!(hull.len(), TEST_AREA_2D_POINTS);
-        assert_approx_eq!(area(&hull), TEST_AREA_2D);
+        assert_approx_eq!(area_points(&hull), TEST_AREA_2D);
     }
 
-    pub(crate) fn check_square(algo: fn(&[f64]) -> Vec<f64>) {
-        let v = get_square();
+    pub(crate) fn check_square(algo: &dyn ConvexHull2d) {
+        let v = from_flat(&get_square());
 
-        let hull = algo(&v);
+        let hull = algo.hull(&v);
 
-        assert_eq!(hull.len(), 8);
-        assert_approx_eq!(area(&hull), 1.0);
+        assert_eq!(hull.len(), 4);
+        assert_approx_eq!(area_points(&hull), 1.0);
     }
 
 
+    pub(crate) fn check_square_indices(algo: fn(&[Point2]) -> Vec<usize>) {
+        let v = from_flat(&get_square());
+
+        let mut hull = algo(&v);
+        let first = hull.iter().position(|&i| i == 0).expect("corner missing");
+        hull.rotate_left(first);
+
+        // corners in counter-clockwise order
+        assert_eq!(hull, vec![0, 1, 3, 2]);
+
+        // all points identical
+        let v = vec![Point2::new(0.5, 0.25); 5];
+        assert_eq!(algo(&v).len(), 1);
+    }
+
+    pub(crate) fn check_square_boundary(algo: &dyn ConvexHull2d) {
+        let mut v = from_flat(&get_square());
+        v.extend(from_flat(&[
+            0.0, 0.25,
+            1.0, 0.5,
+            0.75, 1.0,
+            0.5, 0.0,
+            // duplicates of a boundary point and a corner
+            0.0, 0.25,
+            0.0, 0.0,
+        ]));
+
+        assert_eq!(algo.hull_with(&v, Boundary::Extreme).len(), 4);
+
+        let mut hull = algo.hull_with(&v, Boundary::All);
+        let first = hull.iter().position(|&p| p == Point2::new(0., 0.)).expect("corner missing");
+        hull.rotate_left(first);
+
+        // counter-clockwise, each point exactly once
+        let expected = from_flat(&[
+            0.0, 0.0,
+            0.5, 0.0,
+            1.0, 0.0,
+            1.0, 0.5,
+            1.0, 1.0,
+            0.75, 1.0,
+            0.5, 1.0,
+            0.0, 1.0,
+            0.0, 0.25,
+        ]);
+        assert_eq!(hull, expected);
+
+        // degenerate to a segment, every point is on the boundary
+        let line: Vec<Point2> = (0..10).map(|i| Point2::new(i as f64, 2. * i as f64)).collect();
+        let mut hull = algo.hull_with(&line, Boundary::All);
+        if hull[0] != line[0] {
+            hull.reverse();
+        }
+        assert_eq!(hull, line);
+    }
+
     pub(crate) fn get_square() -> Vec<f64> {
         vec![
             0.0, 0.0,
@@ -167,6 +227,14 @@ pub mod tests {
         ]
     }
 
+    /// `n` random points of the lattice `[-r, r]^3`, which are full of
+    /// coplanar and collinear points and duplicates
+    pub(crate) fn get_random_lattice_3d(seed: u64, n: usize, r: i32) -> Vec<Point3> {
+        let mut rng: Pcg64 = SeedableRng::seed_from_u64(seed);
+        let mut c = || rng.gen_range(-r..=r) as f64;
+        (0..n).map(|_| Point3::new(c(), c(), c())).collect()
+    }
+
     pub(crate) fn get_cube() -> Vec<Point3> {
         // cube and smaller cube inside
         vec![
@@ -4,7 +4,7 @@ use std::path::Path;
 use std::fmt::Write as FmtWrite;
 use std::io::Write as IoWrite;
 
-use itertools::Itertools;
+use crate::d2::{Point2, from_flat};
 
 pub struct SVG {
     buffer: String,
@@ -29,7 +29,7 @@ impl SVG {
         if let Some(dir) = path.parent() {
             std::fs::create_dir_all(dir)?;
         }
-        let mut file = File::create(&path)?;
+        let mut file = File::create(path)?;
 
         writeln!(self.buffer, "</svg>").expect("write error");
 
@@ -37,36 +37,28 @@ impl SVG {
         Ok(())
     }
 
-    pub fn points(&mut self, pointset: &[f64], color: &str) {
-        for i in pointset.iter().tuples::<(_, _)>() {
-            writeln!(self.buffer, "<circle cx='{}' cy='{}' r='0.01' stroke='black' stroke-width='0' fill='{}' />", i.0, i.1, color).expect("write error");
+    pub fn points(&mut self, pointset: &[Point2], color: &str) {
+        for i in pointset.iter() {
+            writeln!(self.buffer, "<circle cx='{}' cy='{}' r='0.01' stroke='black' stroke-width='0' fill='{}' />", i.x, i.y, color).expect("write error");
         }
     }
 
-    pub fn lines(&mut self, points: &[f64], color: &str) {
-        for (a, b) in points.iter()
-                            .tuples::<(_, _)>()
-                            .tuple_windows::<(_, _)>()
-        {
-            writeln!(self.buffer, "<line x1='{}' x2='{}' y1='{}' y2='{}' stroke='{}' stroke-width='0.002' />\n", a.0, b.0, a.1, b.1, color).expect("write error");
+    pub fn lines(&mut self, points: &[Point2], color: &str) {
+        for (a, b) in points.iter().zip(points.iter().skip(1)) {
+            writeln!(self.buffer, "<line x1='{}' x2='{}' y1='{}' y2='{}' stroke='{}' stroke-width='0.002' />\n", a.x, b.x, a.y, b.y, color).expect("write error");
         }
     }
 
-    pub fn dashed_lines(&mut self, points: &[f64], color: &str) {
-        for (a, b) in points.iter()
-                            .tuples::<(_, _)>()
-                            .tuple_windows::<(_, _)>()
-        {
-            writeln!(self.buffer, "<line x1='{}' x2='{}' y1='{}' y2='{}' stroke-dasharray='0.03,0.02' stroke='{}' stroke-width='0.005' />\n", a.0, b.0, a.1, b.1, color).expect("write error");
+    pub fn dashed_lines(&mut self, points: &[Point2], color: &str) {
+        for (a, b) in points.iter().zip(points.iter().skip(1)) {
+            writeln!(self.buffer, "<line x1='{}' x2='{}' y1='{}' y2='{}' stroke-dasharray='0.03,0.02' stroke='{}' stroke-width='0.005' />\n", a.x, b.x, a.y, b.y, color).expect("write error");
         }
     }
 
-    pub fn polygon(&mut self, points: &[f64], color: &str) {
+    pub fn polygon(&mut self, points: &[Point2], color: &str) {
         writeln!(self.buffer, "<polygon fill='none' points='").expect("write error");
-        for a in points.iter()
-            .tuples::<(_, _)>()
-        {
-            writeln!(self.buffer, "{},{} ", a.0, a.1).expect("write error");
+        for a in points.iter() {
+            writeln!(self.buffer, "{},{} ", a.x, a.y).expect("write error");
         }
         writeln!(self.buffer, "' stroke='{}' stroke-width='0.002' />", color).expect("write error");
     }
@@ -78,10 +70,11 @@ impl Default for SVG {
     }
 }
 
+// points stores a contiguous array of 2N floats in the format x1, y1, x2, y2, ...
 pub fn svg(pointset: &[f64], hull: &[f64], name: &str) -> Result<(), io::Error> {
     let mut s = SVG::new();
-    s.points(pointset, "black");
-    s.polygon(hull, "red");
+    s.points(&from_flat(pointset), "black");
+    s.polygon(&from_flat(hull), "red");
 
     let filename = if name.ends_with(".svg") {
         name.to_string()
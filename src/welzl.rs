@@ -0,0 +1,256 @@
+use rand::SeedableRng;
+use rand::seq::SliceRandom;
+use rand_pcg::Pcg64;
+
+use crate::d2::Point2;
+use crate::d3::Point3;
+use crate::andrew::andrew_indices;
+use crate::quickhull3d::try_quickhull3d_indices;
+
+// Welzl, "Smallest enclosing disks (balls and ellipsoids)",
+// New Results and New Trends in Computer Science, LNCS 555, 359 (1991).
+//
+// The points are processed in random order. Whenever a point is outside of
+// the current ball, it has to lie on the boundary of the smallest ball of
+// all points so far, which is found with the same procedure restricted to
+// balls through it. The nesting depth is bounded by the dimension plus one,
+// which results in an expected runtime of O(n).
+
+/// relative slack for points on the boundary, which are otherwise rejected
+/// due to rounding of the centre
+const EPS: f64 = 1e-12;
+
+/// smallest circle enclosing a pointset
+#[derive(Debug, Clone, PartialEq)]
+pub struct Circle {
+    pub centre: Point2,
+    pub radius: f64,
+    /// indices of the points on the boundary, which determine the circle
+    pub support: Vec<usize>,
+}
+
+impl Circle {
+    fn new(pointset: &[Point2], support: &[usize]) -> Circle {
+        let p = |k: usize| pointset[support[k]];
+        let centre = match support.len() {
+            1 => p(0),
+            2 => (p(0) + p(1)) / 2.,
+            _ => {
+                let (b, c) = (p(1) - p(0), p(2) - p(0));
+                let d = 2. * b.cross(c);
+                let (b2, c2) = (b.dot(b), c.dot(c));
+                p(0) + Point2::new(c.y * b2 - b.y * c2, b.x * c2 - c.x * b2) / d
+            }
+        };
+
+        Circle {
+            centre,
+            radius: (p(0) - centre).length(),
+            support: support.to_vec(),
+        }
+    }
+
+    /// whether `p` is inside or on the circle, up to rounding errors
+    pub fn contains(&self, p: Point2) -> bool {
+        let scale = self.radius + self.centre.x.abs() + self.centre.y.abs();
+        (p - self.centre).length() <= self.radius + EPS * scale
+    }
+}
+
+/// smallest sphere enclosing a pointset
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sphere {
+    pub centre: Point3,
+    pub radius: f64,
+    /// indices of the points on the boundary, which determine the sphere
+    pub support: Vec<usize>,
+}
+
+impl Sphere {
+    fn new(pointset: &[Point3], support: &[usize]) -> Sphere {
+        let p = |k: usize| pointset[support[k]];
+        let centre = match support.len() {
+            1 => p(0),
+            2 => (p(0) + p(1)) / 2.,
+            // the circumcircle of the triangle
+            3 => {
+                let (b, c) = (p(1) - p(0), p(2) - p(0));
+                let n = b.cross(c);
+                p(0) + (n.cross(b) * c.dot(c) + c.cross(n) * b.dot(b)) / (2. * n.dot(n))
+            },
+            _ => {
+                let (b, c, d) = (p(1) - p(0), p(2) - p(0), p(3) - p(0));
+                let det = 2. * b.dot(c.cross(d));
+                p(0) + (c.cross(d) * b.dot(b) + d.cross(b) * c.dot(c) + b.cross(c) * d.dot(d)) / det
+            }
+        };
+
+        Sphere {
+            centre,
+            radius: (p(0) - centre).length(),
+            support: support.to_vec(),
+        }
+    }
+
+    /// whether `p` is inside or on the sphere, up to rounding errors
+    pub fn contains(&self, p: Point3) -> bool {
+        let scale = self.radius + self.centre.x.abs() + self.centre.y.abs() + self.centre.z.abs();
+        (p - self.centre).length() <= self.radius + EPS * scale
+    }
+}
+
+/// smallest circle containing all `points`, which are indices into `pointset`,
+/// with the points of `support` on its boundary
+fn circle_with(pointset: &[Point2], points: &[usize], support: &mut Vec<usize>) -> Option<Circle> {
+    let mut circle = if support.is_empty() { None } else { Some(Circle::new(pointset, support)) };
+    for (n, &i) in points.iter().enumerate() {
+        if circle.as_ref().is_some_and(|c| c.contains(pointset[i])) {
+            continue
+        }
+        support.push(i);
+        circle = if support.len() == 3 {
+            Some(Circle::new(pointset, support))
+        } else {
+            circle_with(pointset, &points[..n], support)
+        };
+        support.pop();
+    }
+    circle
+}
+
+/// smallest sphere containing all `points`, which are indices into `pointset`,
+/// with the points of `support` on its boundary
+fn sphere_with(pointset: &[Point3], points: &[usize], support: &mut Vec<usize>) -> Option<Sphere> {
+    let mut sphere = if support.is_empty() { None } else { Some(Sphere::new(pointset, support)) };
+    for (n, &i) in points.iter().enumerate() {
+        if sphere.as_ref().is_some_and(|s| s.contains(pointset[i])) {
+            continue
+        }
+        support.push(i);
+        sphere = if support.len() == 4 {
+            Some(Sphere::new(pointset, support))
+        } else {
+            sphere_with(pointset, &points[..n], support)
+        };
+        support.pop();
+    }
+    sphere
+}
+
+fn shuffled(mut points: Vec<usize>, seed: u64) -> Vec<usize> {
+    let mut rng: Pcg64 = SeedableRng::seed_from_u64(seed);
+    points.shuffle(&mut rng);
+    points
+}
+
+/// smallest circle enclosing all points, `None` for an empty pointset
+///
+/// the order of the points is randomized with `seed`, which makes the
+/// expected runtime O(n) and the result reproducible
+pub fn min_enclosing_circle(pointset: &[Point2], seed: u64) -> Option<Circle> {
+    let points = shuffled((0..pointset.len()).collect(), seed);
+    circle_with(pointset, &points, &mut Vec::new())
+}
+
+/// like `min_enclosing_circle`, but only the vertices of the hull are considered,
+/// which is faster if most points are inside of the hull
+pub fn min_enclosing_circle_hull(pointset: &[Point2], seed: u64) -> Option<Circle> {
+    let points = shuffled(andrew_indices(pointset), seed);
+    circle_with(pointset, &points, &mut Vec::new())
+}
+
+/// smallest sphere enclosing all points, `None` for an empty pointset
+///
+/// the order of the points is randomized with `seed`, which makes the
+/// expected runtime O(n) and the result reproducible
+pub fn min_enclosing_sphere(pointset: &[Point3], seed: u64) -> Option<Sphere> {
+    let points = shuffled((0..pointset.len()).collect(), seed);
+    sphere_with(pointset, &points, &mut Vec::new())
+}
+
+/// like `min_enclosing_sphere`, but only the vertices of the hull are considered,
+/// flat pointsets without a hull are used completely
+pub fn min_enclosing_sphere_hull(pointset: &[Point3], seed: u64) -> Option<Sphere> {
+    let points = match try_quickhull3d_indices(pointset) {
+        Ok(facets) => {
+            let mut vertices: Vec<usize> = facets.into_iter().flatten().collect();
+            vertices.sort_unstable();
+            vertices.dedup();
+            vertices
+        },
+        Err(_) => (0..pointset.len()).collect(),
+    };
+    sphere_with(pointset, &shuffled(points, seed), &mut Vec::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_approx_eq::assert_approx_eq;
+    use crate::d2::from_flat;
+    use crate::util::{get_test_vector_gaussian, get_test_vector_gaussian_3d};
+    use crate::util::tests::{get_square, get_cube};
+
+    #[test]
+    fn circle_square() {
+        let v = from_flat(&get_square());
+        for c in [min_enclosing_circle(&v, 42).unwrap(), min_enclosing_circle_hull(&v, 42).unwrap()] {
+            assert_approx_eq!(c.centre.x, 0.5);
+            assert_approx_eq!(c.centre.y, 0.5);
+            assert_approx_eq!(c.radius, 0.5f64.sqrt());
+            assert!(c.support.len() >= 2);
+        }
+
+        assert!(min_enclosing_circle(&[], 42).is_none());
+        let single = min_enclosing_circle(&v[..1], 42).unwrap();
+        assert_eq!(single.radius, 0.);
+    }
+
+    #[test]
+    fn circle_random() {
+        let v = from_flat(&get_test_vector_gaussian(1000));
+        let circle = min_enclosing_circle(&v, 42).unwrap();
+
+        assert!(v.iter().all(|&p| circle.contains(p)));
+        for &i in &circle.support {
+            assert_approx_eq!((v[i] - circle.centre).length(), circle.radius);
+        }
+        for seed in [1, 2, 3] {
+            assert_approx_eq!(min_enclosing_circle(&v, seed).unwrap().radius, circle.radius);
+            assert_approx_eq!(min_enclosing_circle_hull(&v, seed).unwrap().radius, circle.radius);
+        }
+    }
+
+    #[test]
+    fn sphere_cube() {
+        let v = get_cube();
+        for s in [min_enclosing_sphere(&v, 42).unwrap(), min_enclosing_sphere_hull(&v, 42).unwrap()] {
+            assert_approx_eq!(s.centre.x, 0.5);
+            assert_approx_eq!(s.centre.y, 0.5);
+            assert_approx_eq!(s.centre.z, 0.5);
+            assert_approx_eq!(s.radius, 0.75f64.sqrt());
+        }
+    }
+
+    #[test]
+    fn sphere_random() {
+        let v = get_test_vector_gaussian_3d(2000);
+        let sphere = min_enclosing_sphere(&v, 42).unwrap();
+
+        assert!(v.iter().all(|&p| sphere.contains(p)));
+        assert!(sphere.support.len() >= 2);
+        for &i in &sphere.support {
+            assert_approx_eq!((v[i] - sphere.centre).length(), sphere.radius);
+        }
+        for seed in [1, 2, 3] {
+            assert_approx_eq!(min_enclosing_sphere(&v, seed).unwrap().radius, sphere.radius);
+            assert_approx_eq!(min_enclosing_sphere_hull(&v, seed).unwrap().radius, sphere.radius);
+        }
+
+        // a flat pointset has no hull, but an enclosing sphere
+        let p = Point3::new;
+        let flat = [p(0., 0., 0.), p(2., 0., 0.), p(0., 2., 0.), p(1., 1., 0.)];
+        let s = min_enclosing_sphere_hull(&flat, 42).unwrap();
+        assert_approx_eq!(s.radius, 2f64.sqrt());
+    }
+}